@@ -0,0 +1,70 @@
+// run with `cargo bench`, plain timings without a benchmarking framework
+
+use std::time::{Duration, Instant};
+
+use impact::common::position::Coordinates;
+use impact::objects::object::Object;
+use impact::world::map::{LocalMap, Tile};
+use impact::world::pathfinding::{find_path, FlowField, Movement, PathCache};
+use impact::world::world_elements::{TileType, WorldObject, WorldObjectType};
+
+const MAP_SIZE: u32 = 512;
+const ITERATIONS: u32 = 10;
+
+
+// mixed terrain with rows of rocks that leave a gap on alternating sides, forcing long detours
+fn build_map() -> LocalMap{
+    let mut map = LocalMap::new(MAP_SIZE, MAP_SIZE, TileType::Grass);
+    for y in 0..MAP_SIZE{
+        for x in 0..MAP_SIZE{
+            match (x * 7 + y * 13) % 23{
+                0 => map.set_tile(Coordinates::new(x, y), Tile::new(TileType::Sand)),
+                1 => map.set_tile(Coordinates::new(x, y), Tile::new(TileType::Snow)),
+                _ => {}
+            }
+        }
+    }
+    for y in (16..MAP_SIZE).step_by(32){
+        let gap = if (y / 32) % 2 == 0 { MAP_SIZE - 1 } else { 0 };
+        for x in (0..MAP_SIZE).filter(|x| *x != gap){
+            let rock = Object::new(1, String::from("Rock"), String::from("A big rock"), 500, 200);
+            map.add_object(Coordinates::new(x, y), WorldObject::new(rock, WorldObjectType::Rock));
+        }
+    }
+    map
+}
+
+
+fn bench(name: &str, mut f: impl FnMut()){
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS{
+        let start = Instant::now();
+        f();
+        total += start.elapsed();
+    }
+    println!("{:<40} {:>10.2?} / iteration", name, total / ITERATIONS);
+}
+
+
+fn main(){
+    let map = build_map();
+    let start = Coordinates::new(0, 0);
+    let goal = Coordinates::new(MAP_SIZE - 1, MAP_SIZE - 1);
+
+    bench("a* orthogonal 512x512", || {
+        assert!(find_path(&map, start, goal, Movement::Orthogonal).is_some());
+    });
+    bench("a* diagonal 512x512", || {
+        assert!(find_path(&map, start, goal, Movement::Diagonal).is_some());
+    });
+    bench("flow field diagonal 512x512", || {
+        let flow_field = FlowField::new(&map, goal, Movement::Diagonal);
+        assert!(flow_field.cost_to_goal(start).is_some());
+    });
+
+    let mut cache = PathCache::new();
+    cache.find_path(&map, start, goal, Movement::Diagonal);
+    bench("cached a* diagonal 512x512", || {
+        assert!(cache.find_path(&map, start, goal, Movement::Diagonal).is_some());
+    });
+}
@@ -4,12 +4,48 @@ use crate::common::damage::*;
 use crate::common::position::*;
 use crate::common::stats::Stats;
 use crate::common::status::StatusType;
+use crate::objects::clothing::{Clothing, ClothingPiece};
+use crate::objects::object::Object;
+
+pub const MAX_HEALTH: f32 = 100.0;
+
+// stamina an actor can spend before resting for every point of its stamina stat
+pub const STAMINA_PER_POINT: f32 = 10.0;
+
+// actors are stored by whoever owns them, systems that track relations between actors refer to them with this
+pub type ActorId = u32;
+
+// damage taken every tick from damage over time statuses
+const BURN_DAMAGE_PER_TICK: f32 = 2.0;
+const FREEZE_DAMAGE_PER_TICK: f32 = 2.0;
+const SUFFOCATION_DAMAGE_PER_TICK: f32 = 5.0;
+
+// what a control status forces the actor to do, from the strongest to the weakest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control{
+    Stunned,
+    Asleep,
+    Terrified(ActorId),     // flees from the source
+    Taunted(ActorId),       // attacks the source and nobody else
+    Charmed(ActorId),       // attacks its own allies, never the source
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ActiveStatus{
+    remaining: u32,
+    source: Option<ActorId>,    // actor that caused the status, if any
+}
 
 pub struct Actor{
     body: Body,
     base_stats: Stats,
     stat_modifiers: Stats,
-    statuses: HashMap<StatusType, u32>,
+    statuses: HashMap<StatusType, ActiveStatus>,
+    health: f32,
+    fatigue: f32,       // stamina spent since the actor last rested
+    clothing: Vec<ClothingPiece>,
+    inventory: Vec<Object>,
+    gold: u32,          // coins carried, kept as a count rather than as objects
 }
 
 pub struct Body{
@@ -38,12 +74,198 @@ impl Actor{
             base_stats: base_stats,
             stat_modifiers: Stats::new_zero(),
             statuses: HashMap::new(),
+            health: MAX_HEALTH,
+            fatigue: 0.0,
+            clothing: Vec::new(),
+            inventory: Vec::new(),
+            gold: 0,
+        }
+    }
+
+    pub fn body(&self) -> &Body{
+        &self.body
+    }
+
+    pub fn health(&self) -> f32{
+        self.health
+    }
+
+    pub fn is_dead(&self) -> bool{
+        self.health <= 0.0
+    }
+
+    pub fn fatigue(&self) -> f32{
+        self.fatigue
+    }
+
+    // stamina the actor can still spend, 0 when exhausted
+    pub fn stamina_left(&self) -> f32{
+        (self.get_stats().endurance.stamina as f32 * STAMINA_PER_POINT - self.fatigue).max(0.0)
+    }
+
+    pub fn tire(&mut self, stamina: f32){
+        self.fatigue += stamina.max(0.0);
+    }
+
+    pub fn rest(&mut self, stamina: f32){
+        self.fatigue = (self.fatigue - stamina.max(0.0)).max(0.0);
+    }
+
+    pub fn wear(&mut self, clothing_piece: ClothingPiece){
+        self.clothing.push(clothing_piece);
+    }
+
+    pub fn clothing(&self) -> &[ClothingPiece]{
+        &self.clothing
+    }
+
+    pub fn inventory(&self) -> &[Object]{
+        &self.inventory
+    }
+
+    pub fn add_to_inventory(&mut self, object: Object){
+        self.inventory.push(object);
+    }
+
+    // removes the first object with the given id
+    pub fn remove_from_inventory(&mut self, id: u32) -> Option<Object>{
+        let index = self.inventory.iter().position(|object| object.id == id)?;
+        Some(self.inventory.remove(index))
+    }
+
+    pub fn gold(&self) -> u32{
+        self.gold
+    }
+
+    pub fn add_gold(&mut self, amount: u32){
+        self.gold += amount;
+    }
+
+    // takes coins from the actor, nothing is taken if it doesn't have enough, returns false in that case
+    pub fn take_gold(&mut self, amount: u32) -> bool{
+        if self.gold < amount{
+            return false;
         }
+        self.gold -= amount;
+        true
+    }
+
+    // elemental damage left after going through every piece of clothing worn
+    pub fn protect_element(&self, damage: Damage) -> Damage{
+        self.clothing.iter().fold(damage, |damage, clothing_piece| clothing_piece.protect_element(damage))
     }
 
     pub fn get_stats(&self) -> Stats{
         self.base_stats + self.stat_modifiers
     }
+
+    // stats without the modifiers of what the actor wears or is affected by
+    pub fn base_stats(&self) -> Stats{
+        self.base_stats
+    }
+
+    pub fn base_stats_mut(&mut self) -> &mut Stats{
+        &mut self.base_stats
+    }
+
+    // adds a status for duration ticks, a status that is already active keeps the longest of the two durations
+    pub fn add_status(&mut self, status: StatusType, duration: u32){
+        self.add_status_from(status, duration, None);
+    }
+
+    // like add_status, remembering who caused it, taunt, charm and terror are ignored without a source,
+    // control statuses don't stick to unstoppable or invincible actors
+    pub fn add_status_from(&mut self, status: StatusType, duration: u32, source: Option<ActorId>){
+        if status.is_control() && self.is_uncontrollable(){
+            return;
+        }
+        if status.needs_source() && source.is_none(){
+            return;
+        }
+        let active = self.statuses.entry(status).or_insert(ActiveStatus{ remaining: 0, source });
+        if duration >= active.remaining{
+            *active = ActiveStatus{ remaining: duration, source };
+        }
+    }
+
+    pub fn remove_status(&mut self, status: StatusType){
+        self.statuses.remove(&status);
+    }
+
+    pub fn has_status(&self, status: StatusType) -> bool{
+        self.statuses.contains_key(&status)
+    }
+
+    pub fn status_source(&self, status: StatusType) -> Option<ActorId>{
+        self.statuses.get(&status).and_then(|active| active.source)
+    }
+
+    // the strongest control status the actor is under, None if it's free to act on its own
+    pub fn control(&self) -> Option<Control>{
+        if self.is_uncontrollable(){
+            return None;
+        }
+        if self.has_status(StatusType::Stun){
+            return Some(Control::Stunned);
+        }
+        if self.has_status(StatusType::Sleep){
+            return Some(Control::Asleep);
+        }
+        let sourced = [
+            (StatusType::Terror, Control::Terrified as fn(ActorId) -> Control),
+            (StatusType::Taunt, Control::Taunted),
+            (StatusType::Charm, Control::Charmed),
+        ];
+        sourced.into_iter().find_map(|(status, control)| self.status_source(status).map(control))
+    }
+
+    fn is_uncontrollable(&self) -> bool{
+        self.has_status(StatusType::Unstoppable) || self.has_status(StatusType::Invincible)
+    }
+
+    // advances statuses by one tick, applying damage over time and removing the expired ones
+    pub fn tick_statuses(&mut self) -> Vec<DamageResult>{
+        let mut damages = Vec::new();
+        if self.has_status(StatusType::Burn){
+            damages.push(Damage{ damage_type: DamageType::Elemental(ElementalDamageType::Heat), amount: BURN_DAMAGE_PER_TICK });
+        }
+        if self.has_status(StatusType::Freeze){
+            damages.push(Damage{ damage_type: DamageType::Elemental(ElementalDamageType::Cold), amount: FREEZE_DAMAGE_PER_TICK });
+        }
+        let mut results: Vec<DamageResult> = damages.into_iter().map(|damage| self.damage(damage)).collect();
+        if self.has_status(StatusType::Suffocating){
+            results.push(self.lose_health(SUFFOCATION_DAMAGE_PER_TICK));
+        }
+
+        self.statuses.retain(|_, active| {
+            active.remaining = active.remaining.saturating_sub(1);
+            active.remaining > 0
+        });
+        results
+    }
+
+    fn lose_health(&mut self, amount: f32) -> DamageResult{
+        if self.has_status(StatusType::Invincible) || self.has_status(StatusType::Ethereal){
+            return DamageResult::NoDamage;
+        }
+        self.health -= amount;
+        if self.health <= 0.0{
+            if self.has_status(StatusType::Immortal){
+                self.health = 0.0;
+                return DamageResult::Damage(amount);
+            }
+            return DamageResult::Killed;
+        }
+        DamageResult::Damage(amount)
+    }
+}
+
+
+impl Damageable for Actor{
+    fn damage(&mut self, damage: Damage) -> DamageResult{
+        let damage = self.protect_element(damage);
+        self.lose_health(damage.amount)
+    }
 }
 
 
@@ -65,6 +287,28 @@ impl Body{
             n_able_arts_available: n_able_arts_available,
         }
     }
+
+    pub fn height(&self) -> u32{
+        self.height
+    }
+
+    pub fn weight(&self) -> u32{
+        self.weight
+    }
+
+    pub fn body_parts(&self) -> &[BodyPart]{
+        &self.body_parts
+    }
+
+    // parts that can hold a weapon
+    pub fn able_arts(&self) -> u32{
+        self.n_able_arts
+    }
+
+    // parts that can hold a weapon and aren't holding anything
+    pub fn able_arts_available(&self) -> u32{
+        self.n_able_arts_available
+    }
 }
 
 
@@ -91,6 +335,31 @@ impl BodyPart{
             y_position: y_position,
         }
     }
+
+    pub fn name(&self) -> &str{
+        &self.name
+    }
+
+    pub fn is_vital(&self) -> bool{
+        self.vital
+    }
+
+    pub fn can_grab(&self) -> bool{
+        self.can_grab
+    }
+
+    // anything less than the first won't do damage, anything more than the second destroys the part
+    pub fn armor(&self) -> (u32, u32){
+        (self.armor_low, self.armor_high)
+    }
+
+    pub fn x_position(&self) -> XPosition{
+        self.x_position
+    }
+
+    pub fn y_position(&self) -> YPosition{
+        self.y_position
+    }
 }
 
 
@@ -127,6 +396,19 @@ mod tests {
         assert_eq!(actor.stat_modifiers, Stats::new_zero());
     }
 
+    #[test]
+    fn test_actor_fatigue() {
+        let mut stats = Stats::new_zero();
+        stats.endurance.stamina = 2;
+        let mut actor = Actor::new(Body::new(vec![], 180, 70), stats);
+        actor.tire(15.0);
+        assert_eq!(actor.stamina_left(), 2.0 * STAMINA_PER_POINT - 15.0);
+        actor.tire(50.0);
+        assert_eq!(actor.stamina_left(), 0.0);
+        actor.rest(100.0);
+        assert_eq!(actor.fatigue(), 0.0);
+    }
+
     #[test]
     fn test_actor_get_stats() {
         let body = Body::new(vec![], 180, 70);
@@ -139,6 +421,71 @@ mod tests {
         assert_eq!(stats, base_stats + stat_modifiers);
     }
 
+    #[test]
+    fn test_actor_statuses() {
+        let mut actor = Actor::new(Body::new(vec![], 180, 70), Stats::new_random());
+        actor.add_status(StatusType::Burn, 5);
+        actor.add_status(StatusType::Burn, 2);
+        assert_eq!(actor.statuses[&StatusType::Burn].remaining, 5);
+        assert!(actor.has_status(StatusType::Burn));
+        actor.remove_status(StatusType::Burn);
+        assert!(!actor.has_status(StatusType::Burn));
+    }
+
+    #[test]
+    fn test_control_statuses() {
+        let mut actor = Actor::new(Body::new(vec![], 180, 70), Stats::new_random());
+        actor.add_status(StatusType::Taunt, 5);
+        assert!(!actor.has_status(StatusType::Taunt));
+        actor.add_status_from(StatusType::Charm, 5, Some(3));
+        actor.add_status_from(StatusType::Taunt, 5, Some(2));
+        assert_eq!(actor.control(), Some(Control::Taunted(2)));
+        actor.add_status_from(StatusType::Taunt, 8, Some(4));
+        assert_eq!(actor.status_source(StatusType::Taunt), Some(4));
+        actor.add_status(StatusType::Stun, 1);
+        assert_eq!(actor.control(), Some(Control::Stunned));
+
+        actor.add_status(StatusType::Unstoppable, 10);
+        assert_eq!(actor.control(), None);
+        actor.add_status_from(StatusType::Terror, 5, Some(2));
+        assert!(!actor.has_status(StatusType::Terror));
+    }
+
+    #[test]
+    fn test_actor_tick_statuses() {
+        let mut actor = Actor::new(Body::new(vec![], 180, 70), Stats::new_random());
+        actor.add_status(StatusType::Burn, 2);
+        actor.tick_statuses();
+        assert_eq!(actor.health(), MAX_HEALTH - BURN_DAMAGE_PER_TICK);
+        assert!(actor.has_status(StatusType::Burn));
+        actor.tick_statuses();
+        assert!(!actor.has_status(StatusType::Burn));
+    }
+
+    #[test]
+    fn test_actor_damage() {
+        let mut actor = Actor::new(Body::new(vec![], 180, 70), Stats::new_random());
+        let damage = Damage { amount: 60.0, damage_type: DamageType::Physical(PhysicalDamageType::Slash) };
+        assert_eq!(actor.damage(damage), DamageResult::Damage(60.0));
+        assert_eq!(actor.damage(damage), DamageResult::Killed);
+        assert!(actor.is_dead());
+    }
+
+    #[test]
+    fn test_actor_inventory() {
+        let mut actor = Actor::new(Body::new(vec![], 180, 70), Stats::new_random());
+        actor.add_to_inventory(Object::new(7, String::from("Apple"), String::from("A red apple"), 1, 1));
+        assert_eq!(actor.inventory().len(), 1);
+        assert!(actor.remove_from_inventory(8).is_none());
+        assert_eq!(actor.remove_from_inventory(7).unwrap().name, "Apple");
+        assert!(actor.inventory().is_empty());
+
+        actor.add_gold(5);
+        assert!(!actor.take_gold(6));
+        assert!(actor.take_gold(2));
+        assert_eq!(actor.gold(), 3);
+    }
+
     #[test]
     fn test_body_new() {
         let body_parts = vec![
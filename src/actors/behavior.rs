@@ -0,0 +1,441 @@
+use rand::{Rng, RngCore};
+
+use crate::actors::actor::{Actor, ActorId, Control, MAX_HEALTH};
+use crate::actors::npc::Role;
+use crate::actors::schedule::Activity;
+use crate::common::position::Coordinates;
+use crate::world::sound::HeardSound;
+use crate::world::time::{DayPhase, WorldClock};
+
+// bandits jump out of hiding when their target gets this close
+const AMBUSH_RANGE: u32 = 3;
+
+
+// result of ticking a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status{
+    Success,
+    Failure,
+    Running,
+}
+
+// what the npc decided to do this tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action{
+    Idle,
+    MoveTo(Coordinates),
+    Attack(ActorId),
+    FleeFrom(Coordinates),
+    Hide,
+    Trade,
+    Sleep,
+    Work,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition{
+    EnemyInSight,
+    EnemyWithin(u32),
+    HealthBelow(f32),       // fraction of the max health
+    HeardSomething,         // heard a sound this tick or is still looking for one heard before
+    IsNight,
+    TimeToSleep,            // the schedule says so, without a schedule when it's night
+    TimeToRest,             // the schedule says so
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Leaf{
+    AttackNearestEnemy,
+    FleeFromNearestEnemy,
+    Patrol,
+    Investigate,    // go where the last sound came from
+    Ambush,         // stay hidden and still
+    Trade,
+    Sleep,
+    Work,
+    Rest,           // idle at the post
+    Idle,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node{
+    Sequence(Vec<Node>),        // succeeds if all children succeed, stops at the first that doesn't
+    Selector(Vec<Node>),        // succeeds at the first child that doesn't fail
+    Inverter(Box<Node>),        // swaps success and failure
+    Chance(f64, Box<Node>),     // runs the child with some probability, fails otherwise
+    Condition(Condition),
+    Action(Leaf),
+}
+
+// what the npc knows about its surroundings this tick
+#[derive(Debug, Clone, PartialEq)]
+pub struct Situation{
+    pub position: Coordinates,
+    pub enemies: Vec<(ActorId, Coordinates)>,   // hostile actors the npc is aware of
+    pub allies: Vec<(ActorId, Coordinates)>,    // friendly actors the npc is aware of
+    pub heard: Vec<HeardSound>,
+    pub clock: WorldClock,
+}
+
+// what the npc remembers between ticks
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Memory{
+    pub patrol_route: Vec<Coordinates>,
+    pub patrol_index: usize,
+    pub investigating: Option<Coordinates>,
+    pub post: Option<Coordinates>,      // where the npc works, trades and sleeps, set by its schedule
+    pub routine: Option<Activity>,      // what the schedule asks for now, None for npcs without one
+}
+
+pub struct Context<'a>{
+    pub actor: &'a Actor,
+    pub situation: &'a Situation,
+    pub memory: &'a mut Memory,
+    pub rng: &'a mut dyn RngCore,
+    pub action: Option<Action>,
+}
+
+
+impl Node{
+    pub fn tick(&self, context: &mut Context) -> Status{
+        match self{
+            Node::Sequence(children) => {
+                for child in children{
+                    match child.tick(context){
+                        Status::Success => continue,
+                        status => return status,
+                    }
+                }
+                Status::Success
+            }
+            Node::Selector(children) => {
+                for child in children{
+                    match child.tick(context){
+                        Status::Failure => continue,
+                        status => return status,
+                    }
+                }
+                Status::Failure
+            }
+            Node::Inverter(child) => match child.tick(context){
+                Status::Success => Status::Failure,
+                Status::Failure => Status::Success,
+                Status::Running => Status::Running,
+            },
+            Node::Chance(probability, child) => {
+                if context.rng.gen_bool(probability.clamp(0.0, 1.0)){
+                    child.tick(context)
+                }
+                else{
+                    Status::Failure
+                }
+            }
+            Node::Condition(condition) => {
+                if condition.check(context){ Status::Success } else { Status::Failure }
+            }
+            Node::Action(leaf) => leaf.run(context),
+        }
+    }
+
+    // the tree an npc with this role uses unless given another one
+    pub fn default_for(role: &Role) -> Node{
+        use Condition::*;
+        use Leaf::*;
+        use Node::{Action as Do, Condition as If, Selector, Sequence};
+
+        let flee_when_hurt = Sequence(vec![If(HealthBelow(0.25)), If(EnemyInSight), Do(FleeFromNearestEnemy)]);
+        let flee_from_enemies = Sequence(vec![If(EnemyInSight), Do(FleeFromNearestEnemy)]);
+        let off_duty = Selector(vec![
+            Sequence(vec![If(TimeToSleep), Do(Sleep)]),
+            Sequence(vec![If(TimeToRest), Do(Rest)]),
+        ]);
+        match role{
+            Role::Bandit => Selector(vec![
+                flee_when_hurt,
+                Sequence(vec![If(EnemyWithin(AMBUSH_RANGE)), Do(AttackNearestEnemy)]),
+                Sequence(vec![If(EnemyInSight), Do(Ambush)]),
+                off_duty,
+                Do(Patrol),
+                Do(Idle),
+            ]),
+            Role::Guard => Selector(vec![
+                flee_when_hurt,
+                Sequence(vec![If(EnemyInSight), Do(AttackNearestEnemy)]),
+                off_duty,
+                Sequence(vec![If(HeardSomething), Do(Investigate)]),
+                Do(Patrol),
+                Do(Idle),
+            ]),
+            Role::Mage => Selector(vec![
+                flee_when_hurt,
+                Sequence(vec![If(EnemyInSight), Do(AttackNearestEnemy)]),
+                off_duty,
+                Do(Idle),
+            ]),
+            Role::Farmer => Selector(vec![flee_from_enemies, off_duty, Do(Work)]),
+            Role::Shopkeeper | Role::Blacksmith => Selector(vec![flee_from_enemies, off_duty, Do(Trade)]),
+            Role::Noble => Selector(vec![flee_from_enemies, off_duty, Do(Idle)]),
+        }
+    }
+}
+
+
+impl Condition{
+    pub fn check(&self, context: &Context) -> bool{
+        let situation = context.situation;
+        match self{
+            Condition::EnemyInSight => !situation.enemies.is_empty(),
+            Condition::EnemyWithin(range) => nearest_enemy(situation).is_some_and(|(_, position)| situation.position.chebyshev_distance(position) <= *range),
+            Condition::HealthBelow(fraction) => context.actor.health() < fraction * MAX_HEALTH,
+            Condition::HeardSomething => !situation.heard.is_empty() || context.memory.investigating.is_some(),
+            Condition::IsNight => situation.clock.phase() == DayPhase::Night,
+            Condition::TimeToSleep => match &context.memory.routine{
+                Some(activity) => *activity == Activity::Sleep,
+                None => situation.clock.phase() == DayPhase::Night,
+            },
+            Condition::TimeToRest => context.memory.routine == Some(Activity::Rest),
+        }
+    }
+}
+
+
+impl Leaf{
+    pub fn run(&self, context: &mut Context) -> Status{
+        let situation = context.situation;
+        let (action, status) = match self{
+            Leaf::AttackNearestEnemy => match nearest_enemy(situation){
+                Some((enemy, _)) => (Action::Attack(enemy), Status::Success),
+                None => return Status::Failure,
+            },
+            Leaf::FleeFromNearestEnemy => match nearest_enemy(situation){
+                Some((_, position)) => (Action::FleeFrom(position), Status::Running),
+                None => return Status::Failure,
+            },
+            Leaf::Patrol => {
+                let memory = &mut *context.memory;
+                if memory.patrol_route.is_empty(){
+                    return Status::Failure;
+                }
+                memory.patrol_index %= memory.patrol_route.len();
+                if memory.patrol_route[memory.patrol_index] == situation.position{
+                    memory.patrol_index = (memory.patrol_index + 1) % memory.patrol_route.len();
+                }
+                (Action::MoveTo(memory.patrol_route[memory.patrol_index]), Status::Running)
+            }
+            Leaf::Investigate => {
+                let loudest = situation.heard.iter().max_by(|a, b| a.loudness.total_cmp(&b.loudness));
+                if let Some(heard) = loudest{
+                    context.memory.investigating = Some(heard.sound.source);
+                }
+                match context.memory.investigating{
+                    Some(source) if source == situation.position => {
+                        context.memory.investigating = None;
+                        return Status::Success;
+                    }
+                    Some(source) => (Action::MoveTo(source), Status::Running),
+                    None => return Status::Failure,
+                }
+            }
+            Leaf::Ambush => (Action::Hide, Status::Running),
+            // work, trade, rest and sleep happen at the npc's post, it walks there first
+            Leaf::Trade | Leaf::Sleep | Leaf::Work | Leaf::Rest if context.memory.post.is_some_and(|post| post != situation.position) => {
+                (Action::MoveTo(context.memory.post.unwrap()), Status::Running)
+            }
+            Leaf::Trade => (Action::Trade, Status::Running),
+            Leaf::Sleep => (Action::Sleep, Status::Running),
+            Leaf::Work => (Action::Work, Status::Running),
+            Leaf::Rest => (Action::Idle, Status::Running),
+            Leaf::Idle => (Action::Idle, Status::Success),
+        };
+        context.action = Some(action);
+        status
+    }
+}
+
+
+fn nearest_enemy(situation: &Situation) -> Option<(ActorId, Coordinates)>{
+    situation.enemies.iter().copied().min_by_key(|(_, position)| situation.position.chebyshev_distance(*position))
+}
+
+
+impl Situation{
+    pub fn new(position: Coordinates, clock: WorldClock) -> Situation{
+        Situation{
+            position,
+            enemies: Vec::new(),
+            allies: Vec::new(),
+            heard: Vec::new(),
+            clock,
+        }
+    }
+}
+
+
+// what a control status forces the npc to do, whatever its behavior would be
+pub fn forced_action(actor: &Actor, situation: &Situation) -> Option<Action>{
+    let position_of = |id: ActorId| situation.enemies.iter().chain(&situation.allies).find(|(other, _)| *other == id).map(|(_, position)| *position);
+    let action = match actor.control()?{
+        Control::Stunned => Action::Idle,
+        Control::Asleep => Action::Sleep,
+        // hides when it can't see what it's afraid of
+        Control::Terrified(source) => position_of(source).map_or(Action::Hide, Action::FleeFrom),
+        Control::Taunted(source) => Action::Attack(source),
+        Control::Charmed(source) => situation.allies.iter()
+            .filter(|(ally, _)| *ally != source)
+            .min_by_key(|(_, position)| situation.position.chebyshev_distance(*position))
+            .map_or(Action::Idle, |(ally, _)| Action::Attack(*ally)),
+    };
+    Some(action)
+}
+
+
+// runs a tree for a tick and returns what the npc decided to do, Idle if nothing was decided,
+// control statuses take over the decision
+pub fn decide(tree: &Node, actor: &Actor, situation: &Situation, memory: &mut Memory, rng: &mut dyn RngCore) -> Action{
+    if let Some(action) = forced_action(actor, situation){
+        return action;
+    }
+    let mut context = Context{
+        actor,
+        situation,
+        memory,
+        rng,
+        action: None,
+    };
+    tree.tick(&mut context);
+    context.action.unwrap_or(Action::Idle)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::actors::actor::Body;
+    use crate::common::damage::{Damage, DamageType, Damageable, PhysicalDamageType};
+    use crate::common::stats::Stats;
+    use crate::common::status::StatusType;
+    use crate::world::sound::Sound;
+
+    use super::*;
+
+    fn actor() -> Actor{
+        Actor::new(Body::new(vec![], 180, 70), Stats::new_zero())
+    }
+
+    fn noon() -> WorldClock{
+        WorldClock::at(0, 12, 0)
+    }
+
+    fn tick(node: &Node, actor: &Actor, situation: &Situation, memory: &mut Memory) -> (Status, Option<Action>){
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut context = Context{ actor, situation, memory, rng: &mut rng, action: None };
+        let status = node.tick(&mut context);
+        (status, context.action)
+    }
+
+    #[test]
+    fn test_sequence_and_selector() {
+        let (actor, situation, mut memory) = (actor(), Situation::new(Coordinates::new(0, 0), noon()), Memory::default());
+        let fail = Node::Condition(Condition::EnemyInSight);
+        let succeed = Node::Action(Leaf::Idle);
+        assert_eq!(tick(&Node::Sequence(vec![succeed.clone(), fail.clone()]), &actor, &situation, &mut memory).0, Status::Failure);
+        assert_eq!(tick(&Node::Selector(vec![fail.clone(), succeed.clone()]), &actor, &situation, &mut memory).0, Status::Success);
+        assert_eq!(tick(&Node::Inverter(Box::new(fail)), &actor, &situation, &mut memory).0, Status::Success);
+        assert_eq!(tick(&Node::Sequence(vec![Node::Action(Leaf::Sleep), succeed]), &actor, &situation, &mut memory).0, Status::Running);
+    }
+
+    #[test]
+    fn test_chance_is_deterministic() {
+        let (actor, situation) = (actor(), Situation::new(Coordinates::new(0, 0), noon()));
+        let node = Node::Chance(0.5, Box::new(Node::Action(Leaf::Work)));
+        let run = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut memory = Memory::default();
+            (0..20).map(|_| decide(&node, &actor, &situation, &mut memory, &mut rng)).collect::<Vec<_>>()
+        };
+        assert_eq!(run(3), run(3));
+        assert!(run(3).contains(&Action::Work) && run(3).contains(&Action::Idle));
+    }
+
+    #[test]
+    fn test_patrol() {
+        let (actor, mut situation) = (actor(), Situation::new(Coordinates::new(0, 0), noon()));
+        let mut memory = Memory{ patrol_route: vec![Coordinates::new(0, 0), Coordinates::new(5, 0)], ..Memory::default() };
+        assert_eq!(tick(&Node::Action(Leaf::Patrol), &actor, &situation, &mut memory), (Status::Running, Some(Action::MoveTo(Coordinates::new(5, 0)))));
+        situation.position = Coordinates::new(5, 0);
+        assert_eq!(tick(&Node::Action(Leaf::Patrol), &actor, &situation, &mut memory).1, Some(Action::MoveTo(Coordinates::new(0, 0))));
+        assert_eq!(tick(&Node::Action(Leaf::Patrol), &actor, &situation, &mut Memory::default()).0, Status::Failure);
+    }
+
+    #[test]
+    fn test_guard_responds_to_noise() {
+        let guard = Node::default_for(&Role::Guard);
+        let (actor, mut situation) = (actor(), Situation::new(Coordinates::new(0, 0), noon()));
+        let mut memory = Memory{ patrol_route: vec![Coordinates::new(3, 3)], ..Memory::default() };
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(decide(&guard, &actor, &situation, &mut memory, &mut rng), Action::MoveTo(Coordinates::new(3, 3)));
+
+        let fight = Sound::combat(Coordinates::new(10, 10), 7);
+        situation.heard.push(HeardSound{ listener: 1, sound: fight, loudness: 5.0 });
+        assert_eq!(decide(&guard, &actor, &situation, &mut memory, &mut rng), Action::MoveTo(Coordinates::new(10, 10)));
+        situation.heard.clear();
+        assert_eq!(decide(&guard, &actor, &situation, &mut memory, &mut rng), Action::MoveTo(Coordinates::new(10, 10)));
+
+        situation.enemies.push((7, Coordinates::new(8, 8)));
+        assert_eq!(decide(&guard, &actor, &situation, &mut memory, &mut rng), Action::Attack(7));
+    }
+
+    #[test]
+    fn test_bandit_ambush() {
+        let bandit = Node::default_for(&Role::Bandit);
+        let (actor, mut situation, mut memory) = (actor(), Situation::new(Coordinates::new(0, 0), noon()), Memory::default());
+        let mut rng = StdRng::seed_from_u64(0);
+        situation.enemies.push((2, Coordinates::new(10, 0)));
+        assert_eq!(decide(&bandit, &actor, &situation, &mut memory, &mut rng), Action::Hide);
+        situation.enemies[0].1 = Coordinates::new(2, 1);
+        assert_eq!(decide(&bandit, &actor, &situation, &mut memory, &mut rng), Action::Attack(2));
+    }
+
+    #[test]
+    fn test_farmer_flees_and_sleeps() {
+        let farmer = Node::default_for(&Role::Farmer);
+        let actor = actor();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut situation = Situation::new(Coordinates::new(0, 0), noon());
+        assert_eq!(decide(&farmer, &actor, &situation, &mut Memory::default(), &mut rng), Action::Work);
+        situation.clock = WorldClock::at(0, 23, 0);
+        assert_eq!(decide(&farmer, &actor, &situation, &mut Memory::default(), &mut rng), Action::Sleep);
+        situation.enemies.push((2, Coordinates::new(4, 4)));
+        assert_eq!(decide(&farmer, &actor, &situation, &mut Memory::default(), &mut rng), Action::FleeFrom(Coordinates::new(4, 4)));
+    }
+
+    #[test]
+    fn test_hurt_guard_flees() {
+        let guard = Node::default_for(&Role::Guard);
+        let mut actor = actor();
+        actor.damage(Damage{ damage_type: DamageType::Physical(PhysicalDamageType::Slash), amount: 80.0 });
+        let mut situation = Situation::new(Coordinates::new(0, 0), noon());
+        situation.enemies.push((2, Coordinates::new(1, 1)));
+        let action = decide(&guard, &actor, &situation, &mut Memory::default(), &mut StdRng::seed_from_u64(0));
+        assert_eq!(action, Action::FleeFrom(Coordinates::new(1, 1)));
+    }
+
+    #[test]
+    fn test_control_overrides_behavior() {
+        let guard = Node::default_for(&Role::Guard);
+        let mut actor = actor();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut situation = Situation::new(Coordinates::new(0, 0), noon());
+        situation.enemies.push((2, Coordinates::new(1, 1)));
+        situation.allies.push((3, Coordinates::new(5, 5)));
+
+        actor.add_status_from(StatusType::Charm, 2, Some(2));
+        assert_eq!(decide(&guard, &actor, &situation, &mut Memory::default(), &mut rng), Action::Attack(3));
+        actor.add_status_from(StatusType::Terror, 2, Some(2));
+        assert_eq!(decide(&guard, &actor, &situation, &mut Memory::default(), &mut rng), Action::FleeFrom(Coordinates::new(1, 1)));
+        actor.add_status(StatusType::Unstoppable, 2);
+        assert_eq!(decide(&guard, &actor, &situation, &mut Memory::default(), &mut rng), Action::Attack(2));
+    }
+}
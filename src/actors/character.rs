@@ -0,0 +1,263 @@
+use crate::actors::actor::Actor;
+use crate::actors::progression::{Practice, Progression, MAX_SUB_STAT};
+use crate::common::stats::SubStat;
+use crate::world::time::WorldClock;
+
+// skill points given every time a character levels up
+pub const SKILL_POINTS_PER_LEVEL: u32 = 3;
+
+// how much a skill point raises a sub-stat
+pub const SKILL_POINT_VALUE: f32 = 0.05;
+
+// experience needed to go from a level to the next grows by this much every level
+const EXPERIENCE_STEP: u32 = 100;
+
+
+// what the character did before the story starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Background{
+    Soldier,
+    Merchant,
+    Farmer,
+    Noble,
+    Scholar,
+    Outlaw,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QuestStatus{
+    Active,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quest{
+    pub id: u32,
+    pub title: String,
+    pub description: String,
+    pub experience_reward: u32,
+    pub status: QuestStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharacterError{
+    NotEnoughSkillPoints,
+    StatMaxed,
+    UnknownQuest,
+    QuestClosed,    // already completed or failed
+    DuplicateQuest,
+}
+
+// an actor played by someone, that grows over time
+pub struct Character{
+    pub name: String,
+    pub background: Background,
+    actor: Actor,
+    experience: u32,
+    level: u32,
+    skill_points: u32,
+    quests: Vec<Quest>,
+    pub progression: Progression,
+}
+
+
+impl Background{
+    pub fn description(&self) -> &'static str{
+        match self{
+            Background::Soldier => "Served in an army and knows how to hold a sword",
+            Background::Merchant => "Bought and sold goods from town to town",
+            Background::Farmer => "Worked the fields since childhood",
+            Background::Noble => "Born into a wealthy and influential family",
+            Background::Scholar => "Spent years among books",
+            Background::Outlaw => "Lived outside the law, on the roads and in the woods",
+        }
+    }
+}
+
+
+impl Quest{
+    pub fn new(id: u32, title: &str, description: &str, experience_reward: u32) -> Quest{
+        Quest{
+            id,
+            title: String::from(title),
+            description: String::from(description),
+            experience_reward,
+            status: QuestStatus::Active,
+        }
+    }
+}
+
+
+// total experience needed to reach a level, level 1 needs none
+pub fn experience_for_level(level: u32) -> u32{
+    EXPERIENCE_STEP * level * level.saturating_sub(1) / 2
+}
+
+
+impl Character{
+    pub fn new(name: &str, background: Background, actor: Actor) -> Character{
+        Character{
+            name: String::from(name),
+            background,
+            actor,
+            experience: 0,
+            level: 1,
+            skill_points: 0,
+            quests: Vec::new(),
+            progression: Progression::new(),
+        }
+    }
+
+    pub fn actor(&self) -> &Actor{
+        &self.actor
+    }
+
+    pub fn actor_mut(&mut self) -> &mut Actor{
+        &mut self.actor
+    }
+
+    pub fn experience(&self) -> u32{
+        self.experience
+    }
+
+    pub fn level(&self) -> u32{
+        self.level
+    }
+
+    pub fn skill_points(&self) -> u32{
+        self.skill_points
+    }
+
+    // returns the number of levels gained
+    pub fn gain_experience(&mut self, amount: u32) -> u32{
+        self.experience += amount;
+        let mut levels = 0;
+        while self.experience >= experience_for_level(self.level + 1){
+            self.level += 1;
+            self.skill_points += SKILL_POINTS_PER_LEVEL;
+            levels += 1;
+        }
+        levels
+    }
+
+    // raises a sub-stat of the actor, returns its new value
+    pub fn spend_skill_point(&mut self, sub_stat: SubStat) -> Result<f32, CharacterError>{
+        if self.skill_points == 0{
+            return Err(CharacterError::NotEnoughSkillPoints);
+        }
+        let value = self.actor.base_stats_mut().sub_stat_mut(sub_stat);
+        if *value >= MAX_SUB_STAT{
+            return Err(CharacterError::StatMaxed);
+        }
+        *value = (*value + SKILL_POINT_VALUE).min(MAX_SUB_STAT);
+        self.skill_points -= 1;
+        Ok(*value)
+    }
+
+    // the character got better at a sub-stat by using it, returns how much it grew
+    pub fn practice(&mut self, sub_stat: SubStat, difficulty: f32, succeeded: bool, clock: &WorldClock) -> f32{
+        self.progression.practice(&mut self.actor, sub_stat, difficulty, succeeded, clock)
+    }
+
+    // the character learns from the practice an action handed back, returns how much the sub-stat grew
+    pub fn learn(&mut self, practice: Practice, clock: &WorldClock) -> f32{
+        self.progression.learn(&mut self.actor, practice, clock)
+    }
+
+    pub fn quests(&self) -> &[Quest]{
+        &self.quests
+    }
+
+    pub fn quest(&self, id: u32) -> Option<&Quest>{
+        self.quests.iter().find(|quest| quest.id == id)
+    }
+
+    pub fn active_quests(&self) -> impl Iterator<Item = &Quest>{
+        self.quests.iter().filter(|quest| quest.status == QuestStatus::Active)
+    }
+
+    pub fn add_quest(&mut self, quest: Quest) -> Result<(), CharacterError>{
+        if self.quest(quest.id).is_some(){
+            return Err(CharacterError::DuplicateQuest);
+        }
+        self.quests.push(quest);
+        Ok(())
+    }
+
+    // gives the quest's experience, returns the number of levels gained
+    pub fn complete_quest(&mut self, id: u32) -> Result<u32, CharacterError>{
+        let reward = self.close_quest(id, QuestStatus::Completed)?;
+        Ok(self.gain_experience(reward))
+    }
+
+    pub fn fail_quest(&mut self, id: u32) -> Result<(), CharacterError>{
+        self.close_quest(id, QuestStatus::Failed).map(|_| ())
+    }
+
+    fn close_quest(&mut self, id: u32, status: QuestStatus) -> Result<u32, CharacterError>{
+        let quest = self.quests.iter_mut().find(|quest| quest.id == id).ok_or(CharacterError::UnknownQuest)?;
+        if quest.status != QuestStatus::Active{
+            return Err(CharacterError::QuestClosed);
+        }
+        quest.status = status;
+        Ok(quest.experience_reward)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::actor::Body;
+    use crate::common::stats::Stats;
+
+    use super::*;
+
+    fn character() -> Character{
+        Character::new("Alda", Background::Farmer, Actor::new(Body::new(vec![], 170, 65), Stats::new_zero()))
+    }
+
+    #[test]
+    fn test_levels() {
+        assert_eq!(experience_for_level(1), 0);
+        assert_eq!(experience_for_level(2), 100);
+        assert_eq!(experience_for_level(3), 300);
+
+        let mut character = character();
+        assert_eq!(character.gain_experience(99), 0);
+        assert_eq!(character.level(), 1);
+        assert_eq!(character.gain_experience(201), 2);
+        assert_eq!(character.level(), 3);
+        assert_eq!(character.skill_points(), 2 * SKILL_POINTS_PER_LEVEL);
+    }
+
+    #[test]
+    fn test_spend_skill_points() {
+        let mut character = character();
+        assert_eq!(character.spend_skill_point(SubStat::Stealth), Err(CharacterError::NotEnoughSkillPoints));
+        character.gain_experience(experience_for_level(2));
+        assert_eq!(character.spend_skill_point(SubStat::Stealth), Ok(SKILL_POINT_VALUE));
+        assert_eq!(character.actor().get_stats().agility.stealth, SKILL_POINT_VALUE);
+        assert_eq!(character.skill_points(), SKILL_POINTS_PER_LEVEL - 1);
+
+        character.actor_mut().base_stats_mut().charisma.persuasion = 1.0;
+        assert_eq!(character.spend_skill_point(SubStat::Persuasion), Err(CharacterError::StatMaxed));
+        assert_eq!(character.skill_points(), SKILL_POINTS_PER_LEVEL - 1);
+    }
+
+    #[test]
+    fn test_quest_log() {
+        let mut character = character();
+        character.add_quest(Quest::new(1, "Wolves", "Kill the wolves eating the sheep", 150)).unwrap();
+        character.add_quest(Quest::new(2, "Lost ring", "Find the miller's ring", 50)).unwrap();
+        assert_eq!(character.add_quest(Quest::new(1, "Again", "", 0)), Err(CharacterError::DuplicateQuest));
+
+        assert_eq!(character.complete_quest(1), Ok(1));
+        assert_eq!(character.experience(), 150);
+        assert_eq!(character.complete_quest(1), Err(CharacterError::QuestClosed));
+        character.fail_quest(2).unwrap();
+        assert_eq!(character.active_quests().count(), 0);
+        assert_eq!(character.quest(2).unwrap().status, QuestStatus::Failed);
+        assert_eq!(character.fail_quest(3), Err(CharacterError::UnknownQuest));
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::actors::actor::{Actor, Body, BodyPart};
+use crate::actors::character::{Background, Character};
+use crate::common::position::{XPosition, YPosition};
+use crate::common::stats::*;
+
+// points to spend on stats when buying them
+pub const POINT_BUDGET: u32 = 30;
+
+// a point raises a sub-stat by this much, an attribute by 1 for ATTRIBUTE_POINT_COST points
+pub const SUB_STAT_PER_POINT: f32 = 0.05;
+pub const ATTRIBUTE_POINT_COST: u32 = 2;
+
+// where stats start before spending points
+const BASE_SUB_STAT: f32 = 0.2;
+const BASE_ATTRIBUTE: u32 = 3;
+
+// nobody starts as a master
+pub const MAX_STARTING_SUB_STAT: f32 = 0.6;
+pub const MAX_STARTING_ATTRIBUTE: u32 = 8;
+
+// armor of body parts with nothing special about them
+const DEFAULT_ARMOR: (u32, u32) = (0, 50);
+
+
+// how a body is put together
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BodyTemplate{
+    Humanoid,   // head, torso, two arms that can grab and two legs
+    Quadruped,  // head, torso, four legs and a tail
+    Winged,     // a humanoid with two wings on its back
+    Centauroid, // the upper body of a humanoid with two arms that can grab on four legs and a tail
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Species{
+    Human,
+    Elf,
+    Dwarf,
+    Centaur,
+    Avian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CreationError{
+    MissingName,
+    MissingBackground,
+    MissingSpecies,
+    HeightOutOfRange{ min: u32, max: u32 },
+    WeightOutOfRange{ min: u32, max: u32 },
+    OverBudget{ spent: u32, budget: u32 },
+    SubStatTooHigh(SubStat),
+    AttributeTooHigh(Attribute),
+    MixedStatMethods,   // points were bought and stats were rolled
+}
+
+pub struct CharacterBuilder{
+    name: String,
+    background: Option<Background>,
+    species: Option<Species>,
+    height: Option<u32>,
+    weight: Option<u32>,
+    sub_stat_points: HashMap<SubStat, u32>,
+    attribute_points: HashMap<Attribute, u32>,
+    roll_seed: Option<u64>,
+}
+
+
+impl BodyTemplate{
+    pub fn body(&self, height: u32, weight: u32) -> Body{
+        let part = |name: &str, vital: bool, can_grab: bool, x: XPosition, y: YPosition| {
+            BodyPart::new(String::from(name), vital, can_grab, DEFAULT_ARMOR.0, DEFAULT_ARMOR.1, x, y)
+        };
+        let mut parts = vec![
+            part("Head", true, false, XPosition::Mid, YPosition::High),
+            part("Torso", true, false, XPosition::Mid, YPosition::Mid),
+        ];
+        match self{
+            BodyTemplate::Humanoid | BodyTemplate::Winged => {
+                parts.extend([
+                    part("Left arm", false, true, XPosition::Left, YPosition::Mid),
+                    part("Right arm", false, true, XPosition::Right, YPosition::Mid),
+                    part("Left leg", false, false, XPosition::Left, YPosition::Low),
+                    part("Right leg", false, false, XPosition::Right, YPosition::Low),
+                ]);
+                if *self == BodyTemplate::Winged{
+                    parts.extend([
+                        part("Left wing", false, false, XPosition::Left, YPosition::High),
+                        part("Right wing", false, false, XPosition::Right, YPosition::High),
+                    ]);
+                }
+            }
+            BodyTemplate::Quadruped | BodyTemplate::Centauroid => {
+                if *self == BodyTemplate::Centauroid{
+                    parts.extend([
+                        part("Left arm", false, true, XPosition::Left, YPosition::Mid),
+                        part("Right arm", false, true, XPosition::Right, YPosition::Mid),
+                    ]);
+                }
+                parts.extend([
+                    part("Front left leg", false, false, XPosition::Left, YPosition::Low),
+                    part("Front right leg", false, false, XPosition::Right, YPosition::Low),
+                    part("Hind left leg", false, false, XPosition::Left, YPosition::Low),
+                    part("Hind right leg", false, false, XPosition::Right, YPosition::Low),
+                    part("Tail", false, false, XPosition::Mid, YPosition::Low),
+                ]);
+            }
+        }
+        Body::new(parts, height, weight)
+    }
+}
+
+
+impl Species{
+    pub fn body_template(&self) -> BodyTemplate{
+        match self{
+            Species::Human | Species::Elf | Species::Dwarf => BodyTemplate::Humanoid,
+            Species::Centaur => BodyTemplate::Centauroid,
+            Species::Avian => BodyTemplate::Winged,
+        }
+    }
+
+    // in cm
+    pub fn height_range(&self) -> (u32, u32){
+        match self{
+            Species::Human => (150, 200),
+            Species::Elf => (165, 210),
+            Species::Dwarf => (115, 150),
+            Species::Centaur => (200, 250),
+            Species::Avian => (140, 180),
+        }
+    }
+
+    // in kg
+    pub fn weight_range(&self) -> (u32, u32){
+        match self{
+            Species::Human => (45, 120),
+            Species::Elf => (45, 90),
+            Species::Dwarf => (60, 110),
+            Species::Centaur => (350, 600),
+            Species::Avian => (30, 60),
+        }
+    }
+}
+
+
+// stats every bought stat starts from
+pub fn base_stats() -> Stats{
+    let mut stats = Stats::new_zero();
+    for sub_stat in SubStat::ALL{
+        *stats.sub_stat_mut(sub_stat) = BASE_SUB_STAT;
+    }
+    for attribute in Attribute::ALL{
+        *stats.attribute_mut(attribute) = BASE_ATTRIBUTE;
+    }
+    stats.endurance.base_stamina_used_for_movement = BASE_ATTRIBUTE;
+    stats.endurance.base_stamina_used_for_fighting = BASE_ATTRIBUTE;
+    stats.endurance.health_regen = 0.003;
+    stats
+}
+
+
+// stats left to chance, always within what point buying allows
+pub fn roll_stats(rng: &mut impl Rng) -> Stats{
+    let mut stats = base_stats();
+    for sub_stat in SubStat::ALL{
+        *stats.sub_stat_mut(sub_stat) = rng.gen_range(0.1..=0.5);
+    }
+    for attribute in Attribute::ALL{
+        *stats.attribute_mut(attribute) = rng.gen_range(2..=6);
+    }
+    stats
+}
+
+
+impl CharacterBuilder{
+    pub fn new(name: &str) -> CharacterBuilder{
+        CharacterBuilder{
+            name: String::from(name),
+            background: None,
+            species: None,
+            height: None,
+            weight: None,
+            sub_stat_points: HashMap::new(),
+            attribute_points: HashMap::new(),
+            roll_seed: None,
+        }
+    }
+
+    pub fn background(mut self, background: Background) -> CharacterBuilder{
+        self.background = Some(background);
+        self
+    }
+
+    pub fn species(mut self, species: Species) -> CharacterBuilder{
+        self.species = Some(species);
+        self
+    }
+
+    // the middle of the species' range if not chosen
+    pub fn height(mut self, height: u32) -> CharacterBuilder{
+        self.height = Some(height);
+        self
+    }
+
+    pub fn weight(mut self, weight: u32) -> CharacterBuilder{
+        self.weight = Some(weight);
+        self
+    }
+
+    pub fn buy(mut self, sub_stat: SubStat, points: u32) -> CharacterBuilder{
+        *self.sub_stat_points.entry(sub_stat).or_insert(0) += points;
+        self
+    }
+
+    pub fn buy_attribute(mut self, attribute: Attribute, levels: u32) -> CharacterBuilder{
+        *self.attribute_points.entry(attribute).or_insert(0) += levels;
+        self
+    }
+
+    // rolls the stats instead of buying them
+    pub fn roll_stats(mut self, seed: u64) -> CharacterBuilder{
+        self.roll_seed = Some(seed);
+        self
+    }
+
+    pub fn points_spent(&self) -> u32{
+        self.sub_stat_points.values().sum::<u32>() + ATTRIBUTE_POINT_COST * self.attribute_points.values().sum::<u32>()
+    }
+
+    fn stats(&self) -> Result<Stats, CreationError>{
+        if let Some(seed) = self.roll_seed{
+            if !self.sub_stat_points.is_empty() || !self.attribute_points.is_empty(){
+                return Err(CreationError::MixedStatMethods);
+            }
+            return Ok(roll_stats(&mut StdRng::seed_from_u64(seed)));
+        }
+        let spent = self.points_spent();
+        if spent > POINT_BUDGET{
+            return Err(CreationError::OverBudget{ spent, budget: POINT_BUDGET });
+        }
+        let mut stats = base_stats();
+        for (sub_stat, points) in &self.sub_stat_points{
+            let value = stats.sub_stat_mut(*sub_stat);
+            *value += *points as f32 * SUB_STAT_PER_POINT;
+            if *value > MAX_STARTING_SUB_STAT + f32::EPSILON{
+                return Err(CreationError::SubStatTooHigh(*sub_stat));
+            }
+        }
+        for (attribute, levels) in &self.attribute_points{
+            let value = stats.attribute_mut(*attribute);
+            *value += levels;
+            if *value > MAX_STARTING_ATTRIBUTE{
+                return Err(CreationError::AttributeTooHigh(*attribute));
+            }
+        }
+        Ok(stats)
+    }
+
+    pub fn build(self) -> Result<Character, CreationError>{
+        if self.name.trim().is_empty(){
+            return Err(CreationError::MissingName);
+        }
+        let background = self.background.ok_or(CreationError::MissingBackground)?;
+        let species = self.species.ok_or(CreationError::MissingSpecies)?;
+        let within = |value: Option<u32>, (min, max): (u32, u32)| {
+            let value = value.unwrap_or((min + max) / 2);
+            (min..=max).contains(&value).then_some(value)
+        };
+        let (min, max) = species.height_range();
+        let height = within(self.height, (min, max)).ok_or(CreationError::HeightOutOfRange{ min, max })?;
+        let (min, max) = species.weight_range();
+        let weight = within(self.weight, (min, max)).ok_or(CreationError::WeightOutOfRange{ min, max })?;
+        let stats = self.stats()?;
+
+        let body = species.body_template().body(height, weight);
+        Ok(Character::new(&self.name, background, Actor::new(body, stats)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_body_templates() {
+        let humanoid = BodyTemplate::Humanoid.body(180, 70);
+        assert_eq!(humanoid.body_parts().len(), 6);
+        assert_eq!(humanoid.able_arts(), 2);
+        let quadruped = BodyTemplate::Quadruped.body(120, 300);
+        assert_eq!(quadruped.able_arts(), 0);
+        assert_eq!(quadruped.body_parts().iter().filter(|part| part.y_position() == YPosition::Low).count(), 5);
+        let winged = BodyTemplate::Winged.body(160, 50);
+        assert!(winged.body_parts().iter().any(|part| part.name() == "Left wing" && part.x_position() == XPosition::Left));
+        assert_eq!(winged.body_parts().iter().filter(|part| part.is_vital()).count(), 2);
+        let centauroid = Species::Centaur.body_template().body(210, 500);
+        assert_eq!(centauroid.able_arts(), 2);
+        assert_eq!(centauroid.body_parts().iter().filter(|part| part.y_position() == YPosition::Low).count(), 5);
+    }
+
+    #[test]
+    fn test_point_buy() {
+        let character = CharacterBuilder::new("Mira")
+            .background(Background::Outlaw)
+            .species(Species::Elf)
+            .buy(SubStat::Stealth, 8)
+            .buy_attribute(Attribute::MaxSprintSpeed, 3)
+            .buy_attribute(Attribute::MaxCarryWeight, 2)
+            .build()
+            .unwrap();
+        let stats = character.actor().get_stats();
+        assert!((stats.agility.stealth - (BASE_SUB_STAT + 8.0 * SUB_STAT_PER_POINT)).abs() < 1e-6);
+        assert_eq!(stats.agility.max_sprint_speed, BASE_ATTRIBUTE + 3);
+        assert_eq!(stats.strength.max_carry_weight, BASE_ATTRIBUTE + 2);
+        assert_eq!(character.actor().body().height(), 187);
+    }
+
+    #[test]
+    fn test_invalid_choices() {
+        let human = || CharacterBuilder::new("Tom").background(Background::Farmer).species(Species::Human);
+        assert_eq!(CharacterBuilder::new(" ").build().err(), Some(CreationError::MissingName));
+        assert_eq!(CharacterBuilder::new("Tom").background(Background::Farmer).build().err(), Some(CreationError::MissingSpecies));
+        assert_eq!(human().height(230).build().err(), Some(CreationError::HeightOutOfRange{ min: 150, max: 200 }));
+        assert_eq!(human().buy(SubStat::Stealth, 31).build().err(), Some(CreationError::OverBudget{ spent: 31, budget: POINT_BUDGET }));
+        assert_eq!(human().buy(SubStat::Stealth, 9).build().err(), Some(CreationError::SubStatTooHigh(SubStat::Stealth)));
+        assert_eq!(human().buy_attribute(Attribute::Stamina, 6).build().err(), Some(CreationError::AttributeTooHigh(Attribute::Stamina)));
+        assert_eq!(human().buy(SubStat::Riding, 1).roll_stats(3).build().err(), Some(CreationError::MixedStatMethods));
+    }
+
+    #[test]
+    fn test_rolled_stats() {
+        let roll = |seed: u64| CharacterBuilder::new("Ash").background(Background::Scholar).species(Species::Dwarf).roll_stats(seed).build().unwrap();
+        let (first, second) = (roll(5), roll(5));
+        assert_eq!(first.actor().get_stats(), second.actor().get_stats());
+        let stats = first.actor().get_stats();
+        assert!(SubStat::ALL.iter().all(|sub_stat| stats.sub_stat(*sub_stat) <= MAX_STARTING_SUB_STAT));
+        assert!(Attribute::ALL.iter().all(|attribute| stats.attribute(*attribute) <= MAX_STARTING_ATTRIBUTE));
+    }
+}
@@ -0,0 +1,377 @@
+use crate::actors::actor::{Actor, Body, BodyPart};
+use crate::common::damage::*;
+use crate::common::position::{XPosition, YPosition};
+use crate::common::stats::Stats;
+use crate::objects::weapons::body::NaturalWeapon;
+
+// the creatures shipped with the game, in the format read by parse_plans
+pub const CREATURES: &str = "
+creature: Wolf
+size: Medium
+height: 80
+weight: 40
+part: Head | vital | mid | high | 2 | 40
+part: Torso | vital | mid | mid | 3 | 50
+part: Front left leg | - | left | low | 1 | 25
+part: Front right leg | - | right | low | 1 | 25
+part: Hind left leg | - | left | low | 1 | 25
+part: Hind right leg | - | right | low | 1 | 25
+part: Tail | - | mid | low | 0 | 10
+weapon: Jaws | Head | pierce | 12 | 8 | 1.5
+
+creature: Bear
+size: Large
+height: 150
+weight: 350
+part: Head | vital | mid | high | 6 | 70
+part: Torso | vital | mid | mid | 8 | 90
+part: Front left leg | - | left | low | 5 | 60
+part: Front right leg | - | right | low | 5 | 60
+part: Hind left leg | - | left | low | 5 | 60
+part: Hind right leg | - | right | low | 5 | 60
+weapon: Claws | Front right leg | slash | 20 | 6 | 1.5
+weapon: Jaws | Head | pierce | 18 | 5 | 1.5
+
+creature: Horse
+size: Large
+height: 170
+weight: 500
+part: Head | vital | mid | high | 2 | 50
+part: Torso | vital | mid | mid | 4 | 80
+part: Front left leg | - | left | low | 2 | 40
+part: Front right leg | - | right | low | 2 | 40
+part: Hind left leg | - | left | low | 2 | 40
+part: Hind right leg | - | right | low | 2 | 40
+part: Tail | - | mid | low | 0 | 10
+weapon: Hooves | Hind left leg | impact | 15 | 4 | 2
+
+creature: Giant spider
+size: Medium
+height: 60
+weight: 30
+part: Head | vital | mid | high | 4 | 30
+part: Abdomen | vital | mid | mid | 2 | 35
+part: Left leg 1 | - | left | low | 3 | 15
+part: Left leg 2 | - | left | low | 3 | 15
+part: Left leg 3 | - | left | low | 3 | 15
+part: Left leg 4 | - | left | low | 3 | 15
+part: Right leg 1 | - | right | low | 3 | 15
+part: Right leg 2 | - | right | low | 3 | 15
+part: Right leg 3 | - | right | low | 3 | 15
+part: Right leg 4 | - | right | low | 3 | 15
+weapon: Fangs | Head | acid | 10 | 7 | 1.5
+
+creature: Dragon
+size: Huge
+height: 600
+weight: 4000
+part: Head | vital | mid | high | 25 | 200
+part: Torso | vital | mid | mid | 30 | 250
+part: Left wing | - | left | high | 10 | 120
+part: Right wing | - | right | high | 10 | 120
+part: Front left leg | grab | left | low | 20 | 180
+part: Front right leg | grab | right | low | 20 | 180
+part: Hind left leg | - | left | low | 20 | 180
+part: Hind right leg | - | right | low | 20 | 180
+part: Tail | - | mid | low | 15 | 150
+weapon: Breath | Head | heat | 60 | 2 | 2
+weapon: Claws | Front right leg | slash | 35 | 5 | 1.5
+weapon: Tail | Tail | impact | 30 | 4 | 1.5
+";
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Size{
+    Tiny,
+    Small,
+    Medium,
+    Large,
+    Huge,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartPlan{
+    pub name: String,
+    pub vital: bool,
+    pub can_grab: bool,
+    pub x_position: XPosition,
+    pub y_position: YPosition,
+    pub armor_low: u32,
+    pub armor_high: u32,
+}
+
+// a natural weapon and the body part it belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeaponPlan{
+    pub name: String,
+    pub body_part: String,
+    pub weapon: NaturalWeapon,
+}
+
+// how a kind of creature is built
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreaturePlan{
+    pub name: String,
+    pub size: Size,
+    pub height: u32,
+    pub weight: u32,
+    pub parts: Vec<PartPlan>,
+    pub weapons: Vec<WeaponPlan>,
+}
+
+// a creature made from a plan
+pub struct Creature{
+    pub name: String,
+    pub size: Size,
+    pub actor: Actor,
+    pub weapons: Vec<WeaponPlan>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanErrorKind{
+    UnknownKey(String),
+    BadValue(String),
+    MissingFields,
+    OutsideCreature,        // a line before the first creature
+    UnknownBodyPart(String),
+    NoVitalPart,
+    ArmorOrder,             // armor_low more than armor_high
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanError{
+    pub line: usize,    // starting from 1
+    pub kind: PlanErrorKind,
+}
+
+
+impl CreaturePlan{
+    pub fn body(&self) -> Body{
+        let parts = self.parts.iter()
+            .map(|part| BodyPart::new(part.name.clone(), part.vital, part.can_grab, part.armor_low, part.armor_high, part.x_position, part.y_position))
+            .collect();
+        Body::new(parts, self.height, self.weight)
+    }
+
+    pub fn spawn(&self, stats: Stats) -> Creature{
+        Creature{
+            name: self.name.clone(),
+            size: self.size,
+            actor: Actor::new(self.body(), stats),
+            weapons: self.weapons.clone(),
+        }
+    }
+}
+
+
+// the creatures in CREATURES, parse them once and look plans up in the result
+pub fn creature_library() -> Vec<CreaturePlan>{
+    parse_plans(CREATURES).expect("the built in creatures are valid")
+}
+
+pub fn creature_plan<'a>(library: &'a [CreaturePlan], name: &str) -> Option<&'a CreaturePlan>{
+    library.iter().find(|plan| plan.name.eq_ignore_ascii_case(name))
+}
+
+
+// reads creature plans, one `key: value` per line, a `creature:` line starts a new plan, empty lines
+// and lines starting with # are skipped, parts and weapons have their fields separated by |
+//   part: name | vital, grab, both separated by a comma or - | left, mid or right | high, mid or low | armor low | armor high
+//   weapon: name | body part | pierce, impact, slash, heat, cold or acid | damage | speed | charged multiplier
+pub fn parse_plans(data: &str) -> Result<Vec<CreaturePlan>, PlanError>{
+    let mut plans: Vec<Draft> = Vec::new();
+    for (index, line) in data.lines().enumerate(){
+        let line_number = index + 1;
+        let error = |kind: PlanErrorKind| PlanError{ line: line_number, kind };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#'){
+            continue;
+        }
+        let (key, value) = line.split_once(':').ok_or(error(PlanErrorKind::MissingFields))?;
+        let (key, value) = (key.trim(), value.trim());
+        if key == "creature"{
+            plans.push(Draft{
+                line: line_number,
+                name: String::from(value),
+                size: None,
+                height: None,
+                weight: None,
+                parts: vec![],
+                weapons: vec![],
+            });
+            continue;
+        }
+        let plan = plans.last_mut().ok_or(error(PlanErrorKind::OutsideCreature))?;
+        let fields: Vec<&str> = value.split('|').map(str::trim).collect();
+        match key{
+            "size" => plan.size = Some(parse_size(value).ok_or(error(PlanErrorKind::BadValue(String::from(value))))?),
+            "height" => plan.height = Some(parse_number(value).map_err(error)?),
+            "weight" => plan.weight = Some(parse_number(value).map_err(error)?),
+            "part" => plan.parts.push(parse_part(&fields).map_err(error)?),
+            "weapon" => plan.weapons.push(parse_weapon(&fields).map_err(error)?),
+            _ => return Err(error(PlanErrorKind::UnknownKey(String::from(key)))),
+        }
+    }
+
+    plans.into_iter().map(Draft::finish).collect()
+}
+
+
+// a plan being read, what wasn't given yet is None
+struct Draft{
+    line: usize,    // of the creature: line
+    name: String,
+    size: Option<Size>,
+    height: Option<u32>,
+    weight: Option<u32>,
+    parts: Vec<PartPlan>,
+    weapons: Vec<WeaponPlan>,
+}
+
+impl Draft{
+    fn finish(self) -> Result<CreaturePlan, PlanError>{
+        let error = |kind: PlanErrorKind| PlanError{ line: self.line, kind };
+        let (Some(size), Some(height), Some(weight)) = (self.size, self.height, self.weight) else{
+            return Err(error(PlanErrorKind::MissingFields));
+        };
+        if !self.parts.iter().any(|part| part.vital){
+            return Err(error(PlanErrorKind::NoVitalPart));
+        }
+        if let Some(weapon) = self.weapons.iter().find(|weapon| !self.parts.iter().any(|part| part.name == weapon.body_part)){
+            return Err(error(PlanErrorKind::UnknownBodyPart(weapon.body_part.clone())));
+        }
+        Ok(CreaturePlan{ name: self.name, size, height, weight, parts: self.parts, weapons: self.weapons })
+    }
+}
+
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, PlanErrorKind>{
+    value.parse().map_err(|_| PlanErrorKind::BadValue(String::from(value)))
+}
+
+fn parse_size(value: &str) -> Option<Size>{
+    match value.to_lowercase().as_str(){
+        "tiny" => Some(Size::Tiny),
+        "small" => Some(Size::Small),
+        "medium" => Some(Size::Medium),
+        "large" => Some(Size::Large),
+        "huge" => Some(Size::Huge),
+        _ => None,
+    }
+}
+
+fn parse_part(fields: &[&str]) -> Result<PartPlan, PlanErrorKind>{
+    let [name, kind, x, y, armor_low, armor_high] = fields else{
+        return Err(PlanErrorKind::MissingFields);
+    };
+    let bad = |value: &str| PlanErrorKind::BadValue(String::from(value));
+    let (mut vital, mut can_grab) = (false, false);
+    if *kind != "-"{
+        for flag in kind.split(',').map(str::trim){
+            match flag{
+                "vital" => vital = true,
+                "grab" => can_grab = true,
+                _ => return Err(bad(flag)),
+            }
+        }
+    }
+    let x_position = match *x{
+        "left" => XPosition::Left,
+        "mid" => XPosition::Mid,
+        "right" => XPosition::Right,
+        _ => return Err(bad(x)),
+    };
+    let y_position = match *y{
+        "high" => YPosition::High,
+        "mid" => YPosition::Mid,
+        "low" => YPosition::Low,
+        _ => return Err(bad(y)),
+    };
+    let (armor_low, armor_high) = (parse_number(armor_low)?, parse_number(armor_high)?);
+    if armor_low > armor_high{
+        return Err(PlanErrorKind::ArmorOrder);
+    }
+    Ok(PartPlan{ name: String::from(*name), vital, can_grab, x_position, y_position, armor_low, armor_high })
+}
+
+fn parse_weapon(fields: &[&str]) -> Result<WeaponPlan, PlanErrorKind>{
+    let [name, body_part, damage_type, damage, speed, multiplier] = fields else{
+        return Err(PlanErrorKind::MissingFields);
+    };
+    let damage_type = match *damage_type{
+        "pierce" => DamageType::Physical(PhysicalDamageType::Pierce),
+        "impact" => DamageType::Physical(PhysicalDamageType::Impact),
+        "slash" => DamageType::Physical(PhysicalDamageType::Slash),
+        "heat" => DamageType::Elemental(ElementalDamageType::Heat),
+        "cold" => DamageType::Elemental(ElementalDamageType::Cold),
+        "acid" => DamageType::Poison(PoisonDamageType::Acid),
+        _ => return Err(PlanErrorKind::BadValue(String::from(*damage_type))),
+    };
+    Ok(WeaponPlan{
+        name: String::from(*name),
+        body_part: String::from(*body_part),
+        weapon: NaturalWeapon::new(damage_type, parse_number(damage)?, parse_number(speed)?, parse_number(multiplier)?),
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::weapons::melee::MeleeAttack;
+
+    use super::*;
+
+    #[test]
+    fn test_library() {
+        let library = creature_library();
+        let names: Vec<&str> = library.iter().map(|plan| plan.name.as_str()).collect();
+        assert_eq!(names, vec!["Wolf", "Bear", "Horse", "Giant spider", "Dragon"]);
+
+        let spider = creature_plan(&library, "giant spider").unwrap();
+        assert_eq!(spider.parts.iter().filter(|part| part.y_position == YPosition::Low).count(), 8);
+        assert_eq!(spider.weapons[0].weapon.damage_type, DamageType::Poison(PoisonDamageType::Acid));
+
+        let dragon = creature_plan(&library, "Dragon").unwrap();
+        assert_eq!(dragon.size, Size::Huge);
+        assert!(dragon.parts.iter().all(|part| part.armor_low >= 10));
+        assert_eq!(dragon.weapons[0].weapon.charged_attack().amount, 120.0);
+    }
+
+    #[test]
+    fn test_spawn() {
+        let library = creature_library();
+        let wolf = creature_plan(&library, "Wolf").unwrap().spawn(Stats::new_zero());
+        assert_eq!(wolf.actor.body().body_parts().len(), 7);
+        assert_eq!(wolf.actor.body().able_arts(), 0);
+        assert_eq!(wolf.actor.body().weight(), 40);
+        assert_eq!(wolf.weapons[0].body_part, "Head");
+        let bear = creature_plan(&library, "Bear").unwrap().spawn(Stats::new_zero());
+        assert!(bear.size > wolf.size);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |data: &str| parse_plans(data).unwrap_err();
+        assert_eq!(error("size: Small"), PlanError{ line: 1, kind: PlanErrorKind::OutsideCreature });
+        assert_eq!(error("creature: Rat\n\nsize: Enormous").kind, PlanErrorKind::BadValue(String::from("Enormous")));
+        assert_eq!(error("creature: Rat\npart: Head | vital | mid | high | 9 | 3").kind, PlanErrorKind::ArmorOrder);
+        assert_eq!(error("creature: Rat\npart: Head | vital | mid"), PlanError{ line: 2, kind: PlanErrorKind::MissingFields });
+        let rat = "creature: Rat\nsize: Tiny\nheight: 10\nweight: 1\n";
+        assert_eq!(error(&format!("{rat}part: Tail | - | mid | low | 0 | 5")).kind, PlanErrorKind::NoVitalPart);
+        assert_eq!(
+            error(&format!("{rat}part: Head | vital | mid | high | 0 | 5\nweapon: Teeth | Mouth | pierce | 2 | 9 | 1")).kind,
+            PlanErrorKind::UnknownBodyPart(String::from("Mouth")),
+        );
+        assert_eq!(error("creature: Rat\ncolor: grey").kind, PlanErrorKind::UnknownKey(String::from("color")));
+        assert_eq!(
+            error("creature: Wolf\nsize: Medium\nheight: 80\n\ncreature: Rat\nheight: 10\nweight: 1\npart: Head | vital | mid | high | 0 | 5"),
+            PlanError{ line: 1, kind: PlanErrorKind::MissingFields },
+        );
+        assert_eq!(error(&format!("{rat}part: Head | vital | mid | high | 0 | 5\ncreature: Mole\nheight: 10")).line, 6);
+        assert_eq!(parse_plans(&format!("{rat}part: Head | vital | mid | high | 0 | 5")).unwrap()[0].weight, 1);
+        let grabbing = parse_plans(&format!("{rat}part: Head | vital, grab | mid | high | 0 | 5")).unwrap();
+        assert!(grabbing[0].parts[0].vital && grabbing[0].parts[0].can_grab);
+        assert_eq!(error(&format!("{rat}part: Head | vital, bite | mid | high | 0 | 5")).kind, PlanErrorKind::BadValue(String::from("bite")));
+        assert!(parse_plans("# nothing here\n").unwrap().is_empty());
+    }
+}
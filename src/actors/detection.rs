@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+
+use crate::actors::actor::{Actor, ActorId};
+use crate::actors::progression::Practice;
+use crate::common::position::Coordinates;
+use crate::common::stats::SubStat;
+use crate::common::status::StatusType;
+use crate::world::lighting::visibility;
+use crate::world::weather::Wind;
+
+// distances, in tiles, over which each sense fades, the bigger the further it reaches
+const SIGHT_RANGE: f32 = 20.0;
+const HEARING_RANGE: f32 = 8.0;
+const SMELL_RANGE: f32 = 4.0;
+
+// awareness gained per tick by an observer with perfect senses of a target in plain view right next to it
+const AWARENESS_GAIN: f32 = 0.5;
+// awareness lost per tick when the target can't be sensed at all
+const AWARENESS_DECAY: f32 = 0.05;
+
+const SUSPICIOUS_THRESHOLD: f32 = 0.3;
+const DETECTED_THRESHOLD: f32 = 1.0;
+
+
+// how fast the target is moving, the faster the noisier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pace{
+    Still,
+    Sneaking,
+    Walking,
+    Running,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Awareness{
+    Unaware,
+    Suspicious,
+    Detected,
+}
+
+// what an observer could sense of a target during a tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Observation{
+    pub observer_position: Coordinates,
+    pub target_position: Coordinates,
+    pub line_of_sight: bool,
+    pub light: f32,             // light on the target's tile
+    pub target_pace: Pace,
+    pub wind: Wind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DetectionEvent{
+    pub observer: ActorId,
+    pub target: ActorId,
+    pub awareness: Awareness,   // the new awareness of the observer, the old one was different
+}
+
+// awareness that every observer has of every actor it has been observing
+pub struct DetectionTracker{
+    awareness: HashMap<(ActorId, ActorId), f32>,
+}
+
+
+impl Pace{
+    pub fn noise(&self) -> f32{
+        match self{
+            Pace::Still => 0.0,
+            Pace::Sneaking => 0.2,
+            Pace::Walking => 0.5,
+            Pace::Running => 1.0,
+        }
+    }
+}
+
+
+// noise made by an actor moving at some pace, heavy equipment makes it louder and stealth quieter
+pub fn noise(actor: &Actor, pace: Pace) -> f32{
+    let equipment_weight: u32 = actor.clothing().iter().map(|clothing_piece| clothing_piece.object.weight).sum();
+    let weight_factor = 1.0 + equipment_weight as f32 / actor.body().weight().max(1) as f32;
+    let stealth = actor.get_stats().agility.stealth.clamp(0.0, 1.0);
+    pace.noise() * weight_factor * (1.0 - 0.8 * stealth)
+}
+
+
+// how much the observer senses the target during a tick, between 0 and about 1
+pub fn sense(observer: &Actor, target: &Actor, observation: &Observation) -> f32{
+    let perception = observer.get_stats().perception;
+    let distance = euclidean_distance(observation.observer_position, observation.target_position);
+
+    let sight = if observation.line_of_sight && !target.has_status(StatusType::Invisible){
+        let stealth = target.get_stats().agility.stealth.clamp(0.0, 1.0);
+        visibility(&perception, observation.light) * (1.0 - 0.5 * stealth) / (1.0 + distance / SIGHT_RANGE)
+    }
+    else{
+        0.0
+    };
+    let hearing = perception.hearing * noise(target, observation.target_pace) / (1.0 + distance / HEARING_RANGE).powi(2);
+    let carried = observation.wind.carry_factor(observation.target_position, observation.observer_position);
+    let smell = perception.smell * carried / (1.0 + distance / SMELL_RANGE).powi(2);
+
+    sight + hearing + smell * 0.5
+}
+
+
+fn euclidean_distance(a: Coordinates, b: Coordinates) -> f32{
+    let (dx, dy) = (a.x.abs_diff(b.x) as f32, a.y.abs_diff(b.y) as f32);
+    (dx * dx + dy * dy).sqrt()
+}
+
+
+impl DetectionTracker{
+    pub fn new() -> DetectionTracker{
+        DetectionTracker{
+            awareness: HashMap::new(),
+        }
+    }
+
+    // awareness level of the observer about the target, 0 if it never noticed it
+    pub fn awareness_level(&self, observer: ActorId, target: ActorId) -> f32{
+        self.awareness.get(&(observer, target)).copied().unwrap_or(0.0)
+    }
+
+    pub fn awareness(&self, observer: ActorId, target: ActorId) -> Awareness{
+        awareness_from_level(self.awareness_level(observer, target))
+    }
+
+    // a target that isn't even suspicious of the attacker can be sneak attacked
+    pub fn can_sneak_attack(&self, attacker: ActorId, target: ActorId) -> bool{
+        self.awareness(target, attacker) == Awareness::Unaware
+    }
+
+    // runs a tick of observation, returns an event if the awareness of the observer changed
+    pub fn observe(
+        &mut self,
+        observer_id: ActorId,
+        observer: &Actor,
+        target_id: ActorId,
+        target: &Actor,
+        observation: &Observation,
+    ) -> Option<DetectionEvent>{
+        let before = self.awareness(observer_id, target_id);
+        let sensed = sense(observer, target, observation);
+        let level = self.awareness.entry((observer_id, target_id)).or_insert(0.0);
+        if sensed > 0.0{
+            *level += sensed * AWARENESS_GAIN;
+        }
+        else{
+            *level -= AWARENESS_DECAY;
+        }
+        *level = level.clamp(0.0, DETECTED_THRESHOLD);
+
+        let after = awareness_from_level(*level);
+        (before != after).then_some(DetectionEvent{
+            observer: observer_id,
+            target: target_id,
+            awareness: after,
+        })
+    }
+
+    // stealth the target practiced while being observed, it failed if it got detected, staying hidden from sharp senses teaches the most
+    pub fn sneaking(&self, observer_id: ActorId, observer: &Actor, target_id: ActorId) -> Practice{
+        let perception = observer.get_stats().perception;
+        let detected = self.awareness(observer_id, target_id) == Awareness::Detected;
+        Practice::new(SubStat::Stealth, (perception.vision + perception.hearing) / 2.0, !detected)
+    }
+
+    // the observer noticed the target for sure, for example by catching it in the act
+    pub fn alert(&mut self, observer: ActorId, target: ActorId){
+        self.awareness.insert((observer, target), DETECTED_THRESHOLD);
+    }
+
+    // the observer got suspicious of the target, an observer that already was or had detected it stays so
+    pub fn suspect(&mut self, observer: ActorId, target: ActorId){
+        let level = self.awareness.entry((observer, target)).or_insert(0.0);
+        *level = level.max(SUSPICIOUS_THRESHOLD);
+    }
+
+    // forgets everything about an actor, for example when it dies or leaves the map
+    pub fn forget(&mut self, actor: ActorId){
+        self.awareness.retain(|(observer, target), _| *observer != actor && *target != actor);
+    }
+}
+
+
+impl Default for DetectionTracker{
+    fn default() -> DetectionTracker{
+        DetectionTracker::new()
+    }
+}
+
+
+fn awareness_from_level(level: f32) -> Awareness{
+    if level >= DETECTED_THRESHOLD{
+        Awareness::Detected
+    }
+    else if level >= SUSPICIOUS_THRESHOLD{
+        Awareness::Suspicious
+    }
+    else{
+        Awareness::Unaware
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::actor::Body;
+    use crate::common::stats::{PerceptionStat, Stats};
+
+    use super::*;
+
+    fn observer() -> Actor{
+        let mut stats = Stats::new_zero();
+        stats.perception = PerceptionStat::new(1.0, 1.0, 0.0, 0.0, 0.2);
+        Actor::new(Body::new(vec![], 180, 70), stats)
+    }
+
+    fn target(stealth: f32) -> Actor{
+        let mut stats = Stats::new_zero();
+        stats.agility.stealth = stealth;
+        Actor::new(Body::new(vec![], 180, 70), stats)
+    }
+
+    fn observation(line_of_sight: bool, light: f32, target_pace: Pace) -> Observation{
+        Observation{
+            observer_position: Coordinates::new(0, 0),
+            target_position: Coordinates::new(5, 0),
+            line_of_sight,
+            light,
+            target_pace,
+            wind: Wind::calm(),
+        }
+    }
+
+    #[test]
+    fn test_detection_in_plain_sight() {
+        let mut tracker = DetectionTracker::new();
+        let (observer, target) = (observer(), target(0.0));
+        let mut events = Vec::new();
+        assert!(tracker.sneaking(1, &observer, 2).succeeded);
+        for _ in 0..10{
+            events.extend(tracker.observe(1, &observer, 2, &target, &observation(true, 1.0, Pace::Walking)));
+        }
+        assert_eq!(tracker.awareness(1, 2), Awareness::Detected);
+        assert_eq!(events.last().unwrap().awareness, Awareness::Detected);
+        assert!(!tracker.can_sneak_attack(2, 1));
+        assert_eq!(tracker.sneaking(1, &observer, 2), Practice::new(SubStat::Stealth, 1.0, false));
+    }
+
+    #[test]
+    fn test_stealth_in_the_dark() {
+        let observer = observer();
+        let sneaky = sense(&observer, &target(1.0), &observation(true, 0.0, Pace::Sneaking));
+        let clumsy = sense(&observer, &target(0.0), &observation(true, 1.0, Pace::Running));
+        assert!(sneaky < clumsy / 4.0);
+    }
+
+    #[test]
+    fn test_invisible_can_still_be_heard() {
+        let observer = observer();
+        let mut invisible = target(0.0);
+        invisible.add_status(StatusType::Invisible, 10);
+        assert_eq!(sense(&observer, &invisible, &observation(true, 1.0, Pace::Still)), 0.0);
+        assert!(sense(&observer, &invisible, &observation(true, 1.0, Pace::Running)) > 0.0);
+    }
+
+    #[test]
+    fn test_scent_carried_by_wind() {
+        let mut stats = Stats::new_zero();
+        stats.perception.smell = 1.0;
+        let dog = Actor::new(Body::new(vec![], 60, 30), stats);
+        let target = target(0.0);
+        let mut downwind = observation(false, 0.0, Pace::Still);
+        downwind.wind = Wind::new(-1.0, 0.0);
+        let mut upwind = downwind;
+        upwind.wind = Wind::new(1.0, 0.0);
+        assert!(sense(&dog, &target, &downwind) > 0.0);
+        assert_eq!(sense(&dog, &target, &upwind), 0.0);
+    }
+
+    #[test]
+    fn test_awareness_decays() {
+        let mut tracker = DetectionTracker::new();
+        let (observer, target) = (observer(), target(0.0));
+        tracker.observe(1, &observer, 2, &target, &observation(true, 1.0, Pace::Walking));
+        assert!(tracker.awareness_level(1, 2) > 0.0);
+        for _ in 0..100{
+            tracker.observe(1, &observer, 2, &target, &observation(false, 1.0, Pace::Still));
+        }
+        assert_eq!(tracker.awareness(1, 2), Awareness::Unaware);
+        assert!(tracker.can_sneak_attack(2, 1));
+    }
+}
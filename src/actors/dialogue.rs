@@ -0,0 +1,525 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::actors::actor::{Actor, ActorId};
+use crate::actors::faction::{Faction, Factions};
+use crate::actors::progression::Practice;
+use crate::common::stats::SubStat;
+use crate::common::status::StatusType;
+
+// odds of a check never go past these, anybody can get lucky or slip
+const MIN_CHECK_CHANCE: f32 = 0.05;
+const MAX_CHECK_CHANCE: f32 = 0.95;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Skill{
+    Intimidation,
+    Persuasion,
+    Deception,
+    Performance,
+}
+
+// a charisma check of the speaker against the listener
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Check{
+    pub skill: Skill,
+    pub difficulty: f32,    // added to the listener's resistance
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect{
+    ChangeReputation(i32),          // with the listener's faction
+    ApplyStatus(StatusType, u32),   // on the listener, caused by the speaker
+    StartCombat,
+}
+
+// where the conversation goes after an option, None ends it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Branch{
+    pub next: Option<u32>,
+    pub effects: Vec<Effect>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DialogueOption{
+    pub text: String,
+    pub check: Option<Check>,   // options without a check always succeed
+    pub success: Branch,
+    pub failure: Branch,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DialogueNode{
+    pub text: String,       // what the listener says
+    pub options: Vec<DialogueOption>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DialogueTree{
+    start: u32,
+    nodes: HashMap<u32, DialogueNode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialogueError{
+    MissingNode(u32),
+    NoSuchOption,
+    Ended,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadErrorKind{
+    UnknownKey(String),
+    BadValue(String),
+    MissingFields,
+    OutsideNode,            // an option before the first node
+    DuplicateNode(u32),
+    MissingNode(u32),       // a branch leads to a node that isn't in the tree
+    NoNodes,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadError{
+    pub line: usize,    // starting from 1
+    pub kind: LoadErrorKind,
+}
+
+// what happened after picking an option
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reply{
+    pub succeeded: bool,
+    pub practice: Option<Practice>,     // of the speaker's skill, if the option needed a check
+    pub combat: bool,   // the listener attacks, the conversation is over
+    pub ended: bool,
+}
+
+// a tree being walked by a speaker talking to a listener
+pub struct Conversation<'a>{
+    tree: &'a DialogueTree,
+    current: Option<u32>,
+    pub speaker: ActorId,
+    pub listener: ActorId,
+    pub listener_faction: Option<Faction>,
+}
+
+
+impl Skill{
+    // what gets better by using the skill in a conversation
+    pub fn sub_stat(&self) -> SubStat{
+        match self{
+            Skill::Intimidation => SubStat::Intimidation,
+            Skill::Persuasion => SubStat::Persuasion,
+            Skill::Deception => SubStat::Deception,
+            Skill::Performance => SubStat::Performance,
+        }
+    }
+
+    pub fn of(&self, actor: &Actor) -> f32{
+        actor.get_stats().sub_stat(self.sub_stat())
+    }
+
+    // how hard the listener is to sway, sound judgment shrugs off threats, a creative mind finds
+    // counterarguments, lies are caught by remembering the facts and watching faces, acts by a good ear
+    pub fn resistance(&self, listener: &Actor) -> f32{
+        let stats = listener.get_stats();
+        match self{
+            Skill::Intimidation => stats.intelligence.application,
+            Skill::Persuasion => stats.intelligence.creativity,
+            Skill::Deception => (stats.intelligence.sensorary_memory + stats.perception.vision) / 2.0,
+            Skill::Performance => stats.perception.hearing,
+        }
+    }
+}
+
+
+impl Check{
+    pub fn chance(&self, speaker: &Actor, listener: &Actor) -> f32{
+        (0.5 + self.skill.of(speaker) - self.skill.resistance(listener) - self.difficulty).clamp(MIN_CHECK_CHANCE, MAX_CHECK_CHANCE)
+    }
+
+    pub fn roll(&self, speaker: &Actor, listener: &Actor, rng: &mut impl Rng) -> bool{
+        rng.gen::<f32>() < self.chance(speaker, listener)
+    }
+
+    // the speaker's skill practiced by the check, the less likely it was to pass the more it teaches
+    pub fn practice(&self, speaker: &Actor, listener: &Actor, succeeded: bool) -> Practice{
+        Practice::new(self.skill.sub_stat(), 1.0 - self.chance(speaker, listener), succeeded)
+    }
+}
+
+
+impl Branch{
+    pub fn to(next: u32) -> Branch{
+        Branch{ next: Some(next), effects: vec![] }
+    }
+
+    pub fn end() -> Branch{
+        Branch{ next: None, effects: vec![] }
+    }
+
+    pub fn with(mut self, effect: Effect) -> Branch{
+        self.effects.push(effect);
+        self
+    }
+}
+
+
+impl DialogueOption{
+    // an option that always goes to the same place
+    pub fn new(text: &str, branch: Branch) -> DialogueOption{
+        DialogueOption{
+            text: String::from(text),
+            check: None,
+            success: branch.clone(),
+            failure: branch,
+        }
+    }
+
+    pub fn with_check(text: &str, skill: Skill, difficulty: f32, success: Branch, failure: Branch) -> DialogueOption{
+        DialogueOption{
+            text: String::from(text),
+            check: Some(Check{ skill, difficulty }),
+            success,
+            failure,
+        }
+    }
+}
+
+
+impl DialogueTree{
+    pub fn new(start: u32) -> DialogueTree{
+        DialogueTree{
+            start,
+            nodes: HashMap::new(),
+        }
+    }
+
+    pub fn add_node(&mut self, id: u32, text: &str, options: Vec<DialogueOption>){
+        self.nodes.insert(id, DialogueNode{ text: String::from(text), options });
+    }
+
+    pub fn node(&self, id: u32) -> Option<&DialogueNode>{
+        self.nodes.get(&id)
+    }
+
+    // every branch has to lead to a node that exists
+    pub fn validate(&self) -> Result<(), DialogueError>{
+        if !self.nodes.contains_key(&self.start){
+            return Err(DialogueError::MissingNode(self.start));
+        }
+        let targets = self.nodes.values()
+            .flat_map(|node| &node.options)
+            .flat_map(|option| [option.success.next, option.failure.next])
+            .flatten();
+        for target in targets{
+            if !self.nodes.contains_key(&target){
+                return Err(DialogueError::MissingNode(target));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn start(&self, speaker: ActorId, listener: ActorId, listener_faction: Option<Faction>) -> Conversation<'_>{
+        Conversation{
+            tree: self,
+            current: Some(self.start),
+            speaker,
+            listener,
+            listener_faction,
+        }
+    }
+}
+
+
+// reads a dialogue tree, one `key: value` per line, conversations start at the first node, empty lines and lines
+// starting with # are skipped, fields are separated by | and the effects of a branch by commas
+//   node: id | what the listener says
+//   option: text | skill difficulty or - | success branch | failure branch, only options with a check have one
+// a branch is the id of the next node or end, then its effects: reputation change, status name ticks or combat
+pub fn parse_tree(data: &str) -> Result<DialogueTree, LoadError>{
+    let mut tree: Option<DialogueTree> = None;
+    let mut current = None;         // node the options are added to
+    let mut targets = Vec::new();   // line and next node of every branch
+    for (index, line) in data.lines().enumerate(){
+        let line_number = index + 1;
+        let error = |kind: LoadErrorKind| LoadError{ line: line_number, kind };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#'){
+            continue;
+        }
+        let (key, value) = line.split_once(':').ok_or(error(LoadErrorKind::MissingFields))?;
+        let fields: Vec<&str> = value.split('|').map(str::trim).collect();
+        match key.trim(){
+            "node" => {
+                let [id, text] = fields[..] else{
+                    return Err(error(LoadErrorKind::MissingFields));
+                };
+                let id = parse_number(id).map_err(error)?;
+                let tree = tree.get_or_insert_with(|| DialogueTree::new(id));
+                if tree.node(id).is_some(){
+                    return Err(error(LoadErrorKind::DuplicateNode(id)));
+                }
+                tree.add_node(id, text, vec![]);
+                current = Some(id);
+            }
+            "option" => {
+                let node = current
+                    .and_then(|id| tree.as_mut()?.nodes.get_mut(&id))
+                    .ok_or(error(LoadErrorKind::OutsideNode))?;
+                let option = parse_option(&fields).map_err(error)?;
+                targets.extend([option.success.next, option.failure.next].into_iter().flatten().map(|next| (line_number, next)));
+                node.options.push(option);
+            }
+            key => return Err(error(LoadErrorKind::UnknownKey(String::from(key)))),
+        }
+    }
+
+    let tree = tree.ok_or(LoadError{ line: data.lines().count(), kind: LoadErrorKind::NoNodes })?;
+    if let Some((line, next)) = targets.into_iter().find(|(_, next)| tree.node(*next).is_none()){
+        return Err(LoadError{ line, kind: LoadErrorKind::MissingNode(next) });
+    }
+    Ok(tree)
+}
+
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, LoadErrorKind>{
+    value.parse().map_err(|_| LoadErrorKind::BadValue(String::from(value)))
+}
+
+fn parse_option(fields: &[&str]) -> Result<DialogueOption, LoadErrorKind>{
+    match fields{
+        [text, "-", branch] => Ok(DialogueOption::new(text, parse_branch(branch)?)),
+        [text, check, success, failure] => {
+            let bad = || LoadErrorKind::BadValue(String::from(*check));
+            let (skill, difficulty) = check.split_once(' ').ok_or_else(bad)?;
+            let skill = match skill{
+                "intimidation" => Skill::Intimidation,
+                "persuasion" => Skill::Persuasion,
+                "deception" => Skill::Deception,
+                "performance" => Skill::Performance,
+                _ => return Err(bad()),
+            };
+            Ok(DialogueOption::with_check(text, skill, parse_number(difficulty.trim())?, parse_branch(success)?, parse_branch(failure)?))
+        }
+        _ => Err(LoadErrorKind::MissingFields),
+    }
+}
+
+fn parse_branch(value: &str) -> Result<Branch, LoadErrorKind>{
+    let mut parts = value.split(',').map(str::trim);
+    let mut branch = match parts.next(){
+        Some("end") => Branch::end(),
+        Some(next) => Branch::to(parse_number(next)?),
+        None => return Err(LoadErrorKind::MissingFields),
+    };
+    for effect in parts{
+        let words: Vec<&str> = effect.split_whitespace().collect();
+        let effect = match words[..]{
+            ["reputation", change] => Effect::ChangeReputation(parse_number(change)?),
+            ["status", status, duration] => Effect::ApplyStatus(parse_status(status)?, parse_number(duration)?),
+            ["combat"] => Effect::StartCombat,
+            _ => return Err(LoadErrorKind::BadValue(String::from(effect))),
+        };
+        branch = branch.with(effect);
+    }
+    Ok(branch)
+}
+
+fn parse_status(value: &str) -> Result<StatusType, LoadErrorKind>{
+    Ok(match value{
+        "stun" => StatusType::Stun,
+        "sleep" => StatusType::Sleep,
+        "taunt" => StatusType::Taunt,
+        "rooted" => StatusType::Rooted,
+        "charm" => StatusType::Charm,
+        "terror" => StatusType::Terror,
+        "burn" => StatusType::Burn,
+        "freeze" => StatusType::Freeze,
+        "suffocating" => StatusType::Suffocating,
+        "invisible" => StatusType::Invisible,
+        "unstoppable" => StatusType::Unstoppable,
+        "invincible" => StatusType::Invincible,
+        "immortal" => StatusType::Immortal,
+        "underwater_breathing" => StatusType::UnderwaterBreathing,
+        "airtight" => StatusType::Airtight,
+        "heat_resistance" => StatusType::HeatResistance,
+        "cold_resistance" => StatusType::ColdResistance,
+        "ethereal" => StatusType::Ethereal,
+        _ => return Err(LoadErrorKind::BadValue(String::from(value))),
+    })
+}
+
+
+impl<'a> Conversation<'a>{
+    // what the listener is saying now, None once the conversation is over
+    pub fn current(&self) -> Option<&'a DialogueNode>{
+        self.current.and_then(|id| self.tree.node(id))
+    }
+
+    pub fn is_over(&self) -> bool{
+        self.current.is_none()
+    }
+
+    // the speaker picks an option, rolling its check if it has one and applying the outcome
+    pub fn choose(
+        &mut self,
+        option: usize,
+        speaker: &Actor,
+        listener: &mut Actor,
+        factions: &mut Factions,
+        rng: &mut impl Rng,
+    ) -> Result<Reply, DialogueError>{
+        let id = self.current.ok_or(DialogueError::Ended)?;
+        let node = self.tree.node(id).ok_or(DialogueError::MissingNode(id))?;
+        let option = node.options.get(option).ok_or(DialogueError::NoSuchOption)?;
+
+        let succeeded = option.check.is_none_or(|check| check.roll(speaker, listener, rng));
+        let practice = option.check.map(|check| check.practice(speaker, listener, succeeded));
+        let branch = if succeeded { &option.success } else { &option.failure };
+        let mut combat = false;
+        for effect in &branch.effects{
+            match effect{
+                Effect::ChangeReputation(change) => {
+                    if let Some(faction) = self.listener_faction{
+                        factions.change_reputation(self.speaker, faction, *change);
+                    }
+                }
+                Effect::ApplyStatus(status, duration) => listener.add_status_from(*status, *duration, Some(self.speaker)),
+                Effect::StartCombat => combat = true,
+            }
+        }
+        self.current = if combat { None } else { branch.next };
+        Ok(Reply{
+            succeeded,
+            practice,
+            combat,
+            ended: self.current.is_none(),
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::actors::actor::{Body, Control};
+    use crate::common::stats::{CharismaStat, Stats};
+
+    use super::*;
+
+    const PLAYER: ActorId = 1;
+    const BANDIT: ActorId = 2;
+
+    fn actor(charisma: CharismaStat) -> Actor{
+        let mut stats = Stats::new_zero();
+        stats.charisma = charisma;
+        Actor::new(Body::new(vec![], 180, 70), stats)
+    }
+
+    // a bandit blocks the road
+    fn ambush() -> DialogueTree{
+        let mut tree = DialogueTree::new(0);
+        tree.add_node(0, "Your money or your life.", vec![
+            DialogueOption::with_check(
+                "Step aside or I'll cut you down.",
+                Skill::Intimidation, 0.0,
+                Branch::end().with(Effect::ApplyStatus(StatusType::Terror, 10)),
+                Branch::end().with(Effect::StartCombat),
+            ),
+            DialogueOption::new("Who are you?", Branch::to(1)),
+            DialogueOption::new("Fine, take it.", Branch::end().with(Effect::ChangeReputation(5))),
+        ]);
+        tree.add_node(1, "Nobody you want to know.", vec![
+            DialogueOption::new("Back to the point.", Branch::to(0)),
+        ]);
+        tree
+    }
+
+    #[test]
+    fn test_parse_tree() {
+        let data = "
+            # a bandit blocks the road
+            node: 0 | Your money or your life.
+            option: Step aside or I'll cut you down. | intimidation 0.0 | end, status terror 10 | end, combat
+            option: Who are you? | - | 1
+            option: Fine, take it. | - | end, reputation 5
+
+            node: 1 | Nobody you want to know.
+            option: Back to the point. | - | 0
+        ";
+        assert_eq!(parse_tree(data), Ok(ambush()));
+    }
+
+    #[test]
+    fn test_parse_tree_errors() {
+        let error = |data: &str| parse_tree(data).unwrap_err();
+        assert_eq!(error("option: Hi. | - | end"), LoadError{ line: 1, kind: LoadErrorKind::OutsideNode });
+        assert_eq!(error("node: 0 | Hi.\nnode: 0 | Hello."), LoadError{ line: 2, kind: LoadErrorKind::DuplicateNode(0) });
+        assert_eq!(error("node: 0 | Hi.\noption: Bye. | - | 3"), LoadError{ line: 2, kind: LoadErrorKind::MissingNode(3) });
+        assert_eq!(
+            error("node: 0 | Hi.\noption: Bye. | charm 0.5 | end | end"),
+            LoadError{ line: 2, kind: LoadErrorKind::BadValue(String::from("charm 0.5")) },
+        );
+        assert_eq!(
+            error("node: 0 | Hi.\n\noption: Bye. | - | end, status dizzy 3"),
+            LoadError{ line: 3, kind: LoadErrorKind::BadValue(String::from("dizzy")) },
+        );
+        assert_eq!(error("node: 0 | Hi.\noption: Bye. | persuasion 0.1 | end"), LoadError{ line: 2, kind: LoadErrorKind::MissingFields });
+        assert_eq!(error("topic: weather"), LoadError{ line: 1, kind: LoadErrorKind::UnknownKey(String::from("topic")) });
+        assert_eq!(error("# nothing"), LoadError{ line: 1, kind: LoadErrorKind::NoNodes });
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(ambush().validate(), Ok(()));
+        let mut broken = ambush();
+        broken.add_node(1, "...", vec![DialogueOption::new("Go on.", Branch::to(7))]);
+        assert_eq!(broken.validate(), Err(DialogueError::MissingNode(7)));
+        assert_eq!(DialogueTree::new(3).validate(), Err(DialogueError::MissingNode(3)));
+    }
+
+    #[test]
+    fn test_walk_the_tree() {
+        let tree = ambush();
+        let (player, mut bandit) = (actor(CharismaStat::new(0.0, 0.0, 0.0, 0.0)), actor(CharismaStat::new(0.0, 0.0, 0.0, 0.0)));
+        let mut factions = Factions::new();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut conversation = tree.start(PLAYER, BANDIT, Some(Faction::Outlaws));
+        conversation.choose(1, &player, &mut bandit, &mut factions, &mut rng).unwrap();
+        assert_eq!(conversation.current().unwrap().text, "Nobody you want to know.");
+        assert_eq!(conversation.choose(4, &player, &mut bandit, &mut factions, &mut rng), Err(DialogueError::NoSuchOption));
+        conversation.choose(0, &player, &mut bandit, &mut factions, &mut rng).unwrap();
+
+        let reply = conversation.choose(2, &player, &mut bandit, &mut factions, &mut rng).unwrap();
+        assert!(reply.ended && !reply.combat);
+        assert_eq!(reply.practice, None);
+        assert_eq!(factions.reputation(PLAYER, Faction::Outlaws), 5);
+        assert_eq!(conversation.choose(0, &player, &mut bandit, &mut factions, &mut rng), Err(DialogueError::Ended));
+    }
+
+    #[test]
+    fn test_intimidation() {
+        let tree = ambush();
+        let (brute, mut bandit) = (actor(CharismaStat::new(1.0, 0.0, 0.0, 0.0)), actor(CharismaStat::new(0.0, 0.0, 0.0, 0.0)));
+        let coward = actor(CharismaStat::new(0.0, 0.0, 0.0, 0.0));
+        let check = Check{ skill: Skill::Intimidation, difficulty: 0.0 };
+        assert!(check.chance(&brute, &bandit) > check.chance(&coward, &bandit));
+
+        let mut factions = Factions::new();
+        let mut rng = StdRng::seed_from_u64(0);
+        let (mut scared, mut fights) = (0, 0);
+        for _ in 0..50{
+            let mut conversation = tree.start(PLAYER, BANDIT, Some(Faction::Outlaws));
+            let reply = conversation.choose(0, &brute, &mut bandit, &mut factions, &mut rng).unwrap();
+            if reply.succeeded { scared += 1 } else { fights += 1 }
+            assert_eq!(reply.combat, !reply.succeeded);
+            assert_eq!(reply.practice, Some(check.practice(&brute, &bandit, reply.succeeded)));
+        }
+        assert!(scared > fights);
+        assert_eq!(bandit.control(), Some(Control::Terrified(PLAYER)));
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+
+use crate::actors::actor::ActorId;
+use crate::actors::behavior::Situation;
+use crate::actors::npc::Role;
+use crate::common::position::Coordinates;
+
+pub const MIN_REPUTATION: i32 = -100;
+pub const MAX_REPUTATION: i32 = 100;
+
+// reputation past these makes a faction treat an actor as an enemy or a friend whatever its allegiance
+const HOSTILE_REPUTATION: i32 = -50;
+const FRIENDLY_REPUTATION: i32 = 50;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Faction{
+    Outlaws,
+    TownWatch,
+    Commoners,
+    Merchants,
+    Nobility,
+    Mages,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Relation{
+    Hostile,
+    Neutral,
+    Friendly,
+}
+
+// something an actor did to a member of a faction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deed{
+    Killed,
+    Attacked,
+    Traded,
+    Helped,
+    Stole,
+}
+
+// how factions see each other and how each of them sees single actors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Factions{
+    relations: HashMap<(Faction, Faction), Relation>,
+    reputations: HashMap<(ActorId, Faction), i32>,
+}
+
+
+impl Role{
+    pub fn faction(&self) -> Faction{
+        match self{
+            Role::Bandit => Faction::Outlaws,
+            Role::Guard => Faction::TownWatch,
+            Role::Farmer => Faction::Commoners,
+            Role::Shopkeeper | Role::Blacksmith => Faction::Merchants,
+            Role::Noble => Faction::Nobility,
+            Role::Mage => Faction::Mages,
+        }
+    }
+}
+
+
+impl Deed{
+    // reputation change with the faction the deed was done to
+    pub fn reputation_change(&self) -> i32{
+        match self{
+            Deed::Killed => -50,
+            Deed::Attacked => -20,
+            Deed::Traded => 2,
+            Deed::Helped => 10,
+            Deed::Stole => -15,
+        }
+    }
+}
+
+
+impl Factions{
+    // outlaws are hostile to everyone in town, the watch is friendly with the people it protects,
+    // mages keep to themselves and anything not listed is neutral
+    pub fn new() -> Factions{
+        let mut factions = Factions{
+            relations: HashMap::new(),
+            reputations: HashMap::new(),
+        };
+        for faction in [Faction::TownWatch, Faction::Commoners, Faction::Merchants, Faction::Nobility]{
+            factions.set_relation(Faction::Outlaws, faction, Relation::Hostile);
+        }
+        for faction in [Faction::Commoners, Faction::Merchants, Faction::Nobility]{
+            factions.set_relation(Faction::TownWatch, faction, Relation::Friendly);
+        }
+        factions.set_relation(Faction::Merchants, Faction::Nobility, Relation::Friendly);
+        factions
+    }
+
+    // relations go both ways
+    pub fn set_relation(&mut self, a: Faction, b: Faction, relation: Relation){
+        self.relations.insert((a.min(b), a.max(b)), relation);
+    }
+
+    // a faction is always friendly with itself
+    pub fn relation(&self, a: Faction, b: Faction) -> Relation{
+        if a == b{
+            return Relation::Friendly;
+        }
+        self.relations.get(&(a.min(b), a.max(b))).copied().unwrap_or(Relation::Neutral)
+    }
+
+    pub fn reputation(&self, actor: ActorId, faction: Faction) -> i32{
+        self.reputations.get(&(actor, faction)).copied().unwrap_or(0)
+    }
+
+    pub fn change_reputation(&mut self, actor: ActorId, faction: Faction, change: i32){
+        let reputation = self.reputations.entry((actor, faction)).or_insert(0);
+        *reputation = (*reputation + change).clamp(MIN_REPUTATION, MAX_REPUTATION);
+    }
+
+    // drops the actor's reputation with the faction low enough for it to be hostile, a lower one stays as it is
+    pub fn turn_hostile(&mut self, actor: ActorId, faction: Faction){
+        let reputation = self.reputation(actor, faction);
+        if reputation > HOSTILE_REPUTATION{
+            self.change_reputation(actor, faction, HOSTILE_REPUTATION - reputation);
+        }
+    }
+
+    // the faction the deed was done to takes it fully, its friends take half of it and its enemies the opposite half
+    pub fn record(&mut self, actor: ActorId, deed: Deed, victim: Faction){
+        let change = deed.reputation_change();
+        for faction in ALL_FACTIONS{
+            let felt = if faction == victim{
+                change
+            }
+            else{
+                match self.relation(victim, faction){
+                    Relation::Friendly => change / 2,
+                    Relation::Hostile => -change / 2,
+                    Relation::Neutral => 0,
+                }
+            };
+            if felt != 0{
+                self.change_reputation(actor, faction, felt);
+            }
+        }
+    }
+
+    // how a faction sees an actor, the actor's own reputation wins over its allegiance when it's high or low enough
+    pub fn attitude(&self, faction: Faction, actor: ActorId, allegiance: Option<Faction>) -> Relation{
+        let reputation = self.reputation(actor, faction);
+        if reputation <= HOSTILE_REPUTATION{
+            Relation::Hostile
+        }
+        else if reputation >= FRIENDLY_REPUTATION{
+            Relation::Friendly
+        }
+        else{
+            allegiance.map_or(Relation::Neutral, |allegiance| self.relation(faction, allegiance))
+        }
+    }
+
+    // sorts the actors around into the enemies and allies of someone belonging to the faction
+    pub fn sort_actors(&self, faction: Faction, others: &[(ActorId, Option<Faction>, Coordinates)], situation: &mut Situation){
+        for (actor, allegiance, position) in others{
+            match self.attitude(faction, *actor, *allegiance){
+                Relation::Hostile => situation.enemies.push((*actor, *position)),
+                Relation::Friendly => situation.allies.push((*actor, *position)),
+                Relation::Neutral => {}
+            }
+        }
+    }
+}
+
+
+impl Default for Factions{
+    fn default() -> Factions{
+        Factions::new()
+    }
+}
+
+
+const ALL_FACTIONS: [Faction; 6] = [
+    Faction::Outlaws,
+    Faction::TownWatch,
+    Faction::Commoners,
+    Faction::Merchants,
+    Faction::Nobility,
+    Faction::Mages,
+];
+
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::actors::actor::{Actor, Body};
+    use crate::actors::behavior::{decide, Action, Memory, Node};
+    use crate::common::stats::Stats;
+    use crate::common::status::StatusType;
+    use crate::world::time::WorldClock;
+
+    use super::*;
+
+    const PLAYER: ActorId = 1;
+
+    #[test]
+    fn test_relations() {
+        let factions = Factions::new();
+        assert_eq!(factions.relation(Faction::TownWatch, Faction::Outlaws), Relation::Hostile);
+        assert_eq!(factions.relation(Faction::Outlaws, Faction::TownWatch), Relation::Hostile);
+        assert_eq!(factions.relation(Faction::Mages, Faction::Nobility), Relation::Neutral);
+        assert_eq!(factions.relation(Faction::Outlaws, Faction::Outlaws), Relation::Friendly);
+        assert_eq!(Role::Guard.faction(), Faction::TownWatch);
+    }
+
+    #[test]
+    fn test_killing_guards_makes_enemies_and_friends() {
+        let mut factions = Factions::new();
+        assert_eq!(factions.attitude(Faction::TownWatch, PLAYER, None), Relation::Neutral);
+        factions.record(PLAYER, Deed::Killed, Faction::TownWatch);
+        assert_eq!(factions.reputation(PLAYER, Faction::TownWatch), -50);
+        assert_eq!(factions.reputation(PLAYER, Faction::Commoners), -25);
+        assert_eq!(factions.reputation(PLAYER, Faction::Outlaws), 25);
+        assert_eq!(factions.reputation(PLAYER, Faction::Mages), 0);
+        assert_eq!(factions.attitude(Faction::TownWatch, PLAYER, Some(Faction::Nobility)), Relation::Hostile);
+
+        for _ in 0..10{
+            factions.record(PLAYER, Deed::Killed, Faction::TownWatch);
+        }
+        assert_eq!(factions.reputation(PLAYER, Faction::TownWatch), MIN_REPUTATION);
+    }
+
+    #[test]
+    fn test_trading_builds_reputation() {
+        let mut factions = Factions::new();
+        for _ in 0..25{
+            factions.record(PLAYER, Deed::Traded, Faction::Merchants);
+        }
+        assert_eq!(factions.attitude(Faction::Merchants, PLAYER, Some(Faction::Outlaws)), Relation::Friendly);
+    }
+
+    #[test]
+    fn test_targets_come_from_factions() {
+        let mut factions = Factions::new();
+        factions.record(PLAYER, Deed::Killed, Faction::TownWatch);
+        let others = [
+            (PLAYER, None, Coordinates::new(4, 0)),
+            (2, Some(Faction::Commoners), Coordinates::new(2, 0)),
+            (3, Some(Faction::Mages), Coordinates::new(1, 0)),
+        ];
+        let mut situation = Situation::new(Coordinates::new(0, 0), WorldClock::at(0, 12, 0));
+        factions.sort_actors(Role::Guard.faction(), &others, &mut situation);
+        assert_eq!(situation.enemies, vec![(PLAYER, Coordinates::new(4, 0))]);
+        assert_eq!(situation.allies, vec![(2, Coordinates::new(2, 0))]);
+
+        let mut guard = Actor::new(Body::new(vec![], 180, 70), Stats::new_zero());
+        let tree = Node::default_for(&Role::Guard);
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(decide(&tree, &guard, &situation, &mut Memory::default(), &mut rng), Action::Attack(PLAYER));
+        guard.add_status_from(StatusType::Charm, 3, Some(PLAYER));
+        assert_eq!(decide(&tree, &guard, &situation, &mut Memory::default(), &mut rng), Action::Attack(2));
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::actors::actor::Actor;
+use crate::actors::creation::BodyTemplate;
+use crate::actors::npc::{Role, NPC};
+use crate::actors::trade::{axe, bread, hammer, sword, torch};
+use crate::common::damage::ElementalDamageType;
+use crate::common::stats::*;
+use crate::objects::clothing::ClothingPiece;
+use crate::objects::object::Object;
+
+// ids of the clothes npcs start with
+pub const CLOTHES_ID: u32 = 1200;
+pub const GAMBESON_ID: u32 = 1201;
+pub const ROBE_ID: u32 = 1202;
+pub const FINE_CLOTHES_ID: u32 = 1203;
+pub const APRON_ID: u32 = 1204;
+pub const DAGGER_ID: u32 = 1205;
+pub const STAFF_ID: u32 = 1206;
+
+// how far a stat goes from the role's average, most npcs are close to it
+const SKILL_SPREAD: f32 = 0.15;
+const ATTRIBUTE_SPREAD: f32 = 2.0;
+
+
+// what npcs of a role are like
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NpcTemplate{
+    pub role: Role,
+    pub height: (u32, u32),     // range in cm
+    pub weight: (u32, u32),     // range in kg
+    pub gold: (u32, u32),       // range of coins carried
+}
+
+
+impl NpcTemplate{
+    pub fn for_role(role: Role) -> NpcTemplate{
+        let (height, weight, gold) = match role{
+            Role::Bandit => ((160, 190), (55, 85), (0, 30)),
+            Role::Guard => ((170, 195), (70, 100), (5, 20)),
+            Role::Shopkeeper => ((155, 185), (55, 95), (20, 60)),
+            Role::Farmer => ((155, 185), (60, 90), (0, 10)),
+            Role::Blacksmith => ((165, 195), (80, 115), (15, 50)),
+            Role::Noble => ((160, 190), (55, 90), (100, 300)),
+            Role::Mage => ((155, 190), (50, 80), (10, 80)),
+        };
+        NpcTemplate{ role, height, weight, gold }
+    }
+
+    // average stats of the role, everyone starts from the same middling stats and gets better at what the role needs
+    pub fn average_stats(&self) -> Stats{
+        let mut stats = Stats::new(
+            StrengthStat::new(5, 5),
+            PerceptionStat::new(0.5, 0.5, 0.3, 0.3, 0.3),
+            EnduranceStat::new(5, 5, 5, 0.003),
+            CharismaStat::new(0.3, 0.3, 0.3, 0.3),
+            IntelligenceStat::new(0.4, 0.3, 0.4, 0.4, 0.3),
+            AgilityStat::new(5, 0.3, 0.2, 0.3, 0.2, 0.2, 0.2, 0.1),
+        );
+        match self.role{
+            Role::Bandit => {
+                stats.agility.stealth += 0.3;
+                stats.agility.pickpocting += 0.3;
+                stats.charisma.intimidation += 0.3;
+                stats.charisma.deception += 0.2;
+                stats.strength.base_melee_force += 1;
+            }
+            Role::Guard => {
+                stats.strength.max_carry_weight += 1;
+                stats.strength.base_melee_force += 3;
+                stats.endurance.stamina += 3;
+                stats.perception.vision += 0.2;
+                stats.perception.hearing += 0.2;
+                stats.charisma.intimidation += 0.2;
+            }
+            Role::Shopkeeper => {
+                stats.charisma.persuasion += 0.4;
+                stats.charisma.deception += 0.2;
+                stats.intelligence.application += 0.2;
+            }
+            Role::Farmer => {
+                stats.strength.max_carry_weight += 2;
+                stats.endurance.stamina += 3;
+                stats.agility.riding += 0.2;
+            }
+            Role::Blacksmith => {
+                stats.strength.max_carry_weight += 2;
+                stats.strength.base_melee_force += 4;
+                stats.endurance.stamina += 2;
+                stats.charisma.persuasion += 0.2;
+            }
+            Role::Noble => {
+                stats.charisma.persuasion += 0.3;
+                stats.charisma.performance += 0.3;
+                stats.intelligence.text_memory += 0.3;
+                stats.agility.riding += 0.4;
+            }
+            Role::Mage => {
+                stats.intelligence.sensorary_memory += 0.3;
+                stats.intelligence.text_memory += 0.4;
+                stats.intelligence.learning += 0.3;
+                stats.intelligence.creativity += 0.4;
+            }
+        }
+        stats
+    }
+
+    // the role's average stats with every stat nudged up or down
+    pub fn roll_stats(&self, rng: &mut impl Rng) -> Stats{
+        let mut stats = self.average_stats();
+        for sub_stat in SubStat::ALL{
+            let value = stats.sub_stat_mut(sub_stat);
+            *value = (*value + bell(rng) * SKILL_SPREAD).clamp(0.0, 1.0);
+        }
+        for value in stats.levels_mut(){
+            *value = (*value as f32 + bell(rng) * ATTRIBUTE_SPREAD).round().clamp(1.0, 10.0) as u32;
+        }
+        stats
+    }
+
+    // clothes worn by the role
+    pub fn clothing(&self) -> Vec<ClothingPiece>{
+        let clothes = |id: u32, name: &str, description: &str, weight: u32, value: u32, protection: &[(ElementalDamageType, f32)]| {
+            let object = Object::new(id, String::from(name), String::from(description), weight, weight * 2).with_value(value);
+            ClothingPiece::new(object, HashMap::from_iter(protection.iter().copied()))
+        };
+        match self.role{
+            Role::Guard => vec![clothes(GAMBESON_ID, "Gambeson", "A padded jacket worn under the watch's colors", 4, 20, &[(ElementalDamageType::Cold, 0.3)])],
+            Role::Blacksmith => vec![clothes(APRON_ID, "Leather apron", "Keeps the sparks off", 3, 8, &[(ElementalDamageType::Heat, 0.5)])],
+            Role::Noble => vec![clothes(FINE_CLOTHES_ID, "Fine clothes", "Silk and velvet", 2, 60, &[])],
+            Role::Mage => vec![clothes(ROBE_ID, "Robe", "Embroidered with warding runes", 2, 30, &[(ElementalDamageType::Heat, 0.2), (ElementalDamageType::Cold, 0.2)])],
+            _ => vec![clothes(CLOTHES_ID, "Clothes", "Simple linen clothes", 1, 3, &[])],
+        }
+    }
+
+    // weapons, tools and supplies carried by the role
+    pub fn equipment(&self) -> Vec<Object>{
+        let item = |id: u32, name: &str, description: &str, weight: u32, value: u32| {
+            Object::new(id, String::from(name), String::from(description), weight, weight).with_value(value)
+        };
+        match self.role{
+            Role::Bandit => vec![item(DAGGER_ID, "Dagger", "Easy to hide", 1, 10), bread()],
+            Role::Guard => vec![sword(), torch()],
+            Role::Farmer => vec![axe(), bread(), bread()],
+            Role::Blacksmith => vec![hammer()],
+            Role::Mage => vec![item(STAFF_ID, "Staff", "Knotted wood humming with power", 3, 50)],
+            Role::Shopkeeper | Role::Noble => vec![bread()],
+        }
+    }
+
+    pub fn generate(&self, rng: &mut impl Rng) -> NPC{
+        let body = BodyTemplate::Humanoid.body(rng.gen_range(self.height.0..=self.height.1), rng.gen_range(self.weight.0..=self.weight.1));
+        let mut actor = Actor::new(body, self.roll_stats(rng));
+        for clothing_piece in self.clothing(){
+            actor.wear(clothing_piece);
+        }
+        for object in self.equipment(){
+            actor.add_to_inventory(object);
+        }
+        actor.add_gold(rng.gen_range(self.gold.0..=self.gold.1));
+        NPC::new(actor, self.role)
+    }
+}
+
+
+// makes npcs from a seed, the same seed always gives the same npcs in the same order
+pub struct NpcGenerator{
+    rng: StdRng,
+}
+
+impl NpcGenerator{
+    pub fn new(seed: u64) -> NpcGenerator{
+        NpcGenerator{
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn generate(&mut self, role: Role) -> NPC{
+        NpcTemplate::for_role(role).generate(&mut self.rng)
+    }
+}
+
+
+// between -1 and 1, values close to 0 are the most likely
+fn bell(rng: &mut impl Rng) -> f32{
+    rng.gen::<f32>() - rng.gen::<f32>()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::trade::SWORD_ID;
+    use crate::world::world_elements::World;
+
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_npcs() {
+        let (mut first, mut second) = (NpcGenerator::new(7), NpcGenerator::new(7));
+        for role in [Role::Guard, Role::Farmer, Role::Mage]{
+            let (a, b) = (first.generate(role), second.generate(role));
+            assert_eq!(a.actor.get_stats(), b.actor.get_stats());
+            assert_eq!(a.actor.inventory(), b.actor.inventory());
+            assert_eq!(a.actor.body().height(), b.actor.body().height());
+        }
+        let mut other = NpcGenerator::new(8);
+        assert_ne!(NpcGenerator::new(7).generate(Role::Guard).actor.get_stats(), other.generate(Role::Guard).actor.get_stats());
+    }
+
+    #[test]
+    fn test_roles_shape_stats() {
+        let mut generator = NpcGenerator::new(1);
+        let average = |role: Role, generator: &mut NpcGenerator, stat: fn(&Stats) -> f32| {
+            (0..50).map(|_| stat(&generator.generate(role).actor.get_stats())).sum::<f32>() / 50.0
+        };
+        let stealth = |stats: &Stats| stats.agility.stealth;
+        assert!(average(Role::Bandit, &mut generator, stealth) > average(Role::Noble, &mut generator, stealth));
+        let creativity = |stats: &Stats| stats.intelligence.creativity;
+        assert!(average(Role::Mage, &mut generator, creativity) > average(Role::Guard, &mut generator, creativity));
+
+        let stats = NpcTemplate::for_role(Role::Shopkeeper).roll_stats(&mut StdRng::seed_from_u64(3));
+        assert!((0.0..=1.0).contains(&stats.charisma.persuasion));
+        assert!((1..=10).contains(&stats.strength.base_melee_force));
+        let farmers: Vec<Stats> = (0..20).map(|_| generator.generate(Role::Farmer).actor.get_stats()).collect();
+        let varied = |stat: fn(&Stats) -> u32| farmers.iter().any(|stats| stat(stats) != stat(&farmers[0]));
+        assert!(varied(|stats| stats.endurance.base_stamina_used_for_movement));
+        assert!(varied(|stats| stats.endurance.base_stamina_used_for_fighting));
+
+        // everyone can carry what they start with
+        for role in [Role::Guard, Role::Farmer, Role::Blacksmith]{
+            let npc = generator.generate(role);
+            let carried: u32 = npc.actor.inventory().iter().map(|object| object.weight).sum();
+            assert!(World::Earth.parameters().max_carry_weight(&npc.actor.get_stats()) >= carried);
+        }
+    }
+
+    #[test]
+    fn test_equipment_and_gold() {
+        let mut generator = NpcGenerator::new(2);
+        let guard = generator.generate(Role::Guard);
+        assert_eq!(guard.role, Role::Guard);
+        assert!(guard.actor.inventory().iter().any(|object| object.id == SWORD_ID));
+        assert_eq!(guard.actor.clothing()[0].object.id, GAMBESON_ID);
+        assert!((5..=20).contains(&guard.actor.gold()));
+
+        let noble = generator.generate(Role::Noble);
+        assert!(noble.actor.gold() >= 100);
+        let (low, high) = NpcTemplate::for_role(Role::Noble).height;
+        assert!((low..=high).contains(&noble.actor.body().height()));
+    }
+}
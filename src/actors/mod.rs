@@ -1,3 +1,15 @@
-mod actor;
-mod character;
-mod npc;
+pub mod actor;
+pub mod behavior;
+pub mod character;
+pub mod creation;
+pub mod creature;
+pub mod detection;
+pub mod dialogue;
+pub mod faction;
+pub mod generation;
+pub mod mount;
+pub mod npc;
+pub mod progression;
+pub mod schedule;
+pub mod theft;
+pub mod trade;
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::actors::actor::{Actor, ActorId, MAX_HEALTH};
+use crate::actors::creature::{Creature, Size};
+use crate::actors::progression::Practice;
+use crate::common::damage::{Damage, DamageResult};
+use crate::common::stats::SubStat;
+use crate::common::status::StatusType;
+use crate::world::map::Map;
+use crate::world::time::WorldClock;
+use crate::world::travel::{travel, Journey, Route};
+
+// damage multiplier of a mounted charge made by a rider with perfect riding, an unskilled rider gets none of it
+const MAX_CHARGE_BONUS: f32 = 1.0;
+
+// a hit on the mount taking away this much of its health is sure to throw an unskilled rider off
+const THROWING_DAMAGE: f32 = 0.25 * MAX_HEALTH;
+
+// ticks a thrown rider spends on the ground
+const THROWN_STUN: u32 = 2;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MountError{
+    TooSmall,           // only large and huge creatures can carry a rider
+    DeadMount,
+    AlreadyRidden,
+    AlreadyRiding,      // the rider is on another mount
+    NotRiding,          // the rider isn't on that mount
+    UnknownActor(ActorId),  // a member of a travelling party, or its mount, wasn't among the actors given
+}
+
+// who is riding what
+pub struct Stables{
+    mounts: HashMap<ActorId, ActorId>,      // rider and its mount
+}
+
+
+impl Creature{
+    pub fn can_be_ridden(&self) -> bool{
+        self.size >= Size::Large && !self.actor.is_dead()
+    }
+}
+
+
+impl Stables{
+    pub fn new() -> Stables{
+        Stables{
+            mounts: HashMap::new(),
+        }
+    }
+
+    pub fn mount_of(&self, rider: ActorId) -> Option<ActorId>{
+        self.mounts.get(&rider).copied()
+    }
+
+    pub fn rider_of(&self, mount: ActorId) -> Option<ActorId>{
+        self.mounts.iter().find(|(_, ridden)| **ridden == mount).map(|(rider, _)| *rider)
+    }
+
+    pub fn mount(&mut self, rider: ActorId, mount_id: ActorId, mount: &Creature) -> Result<(), MountError>{
+        if mount.actor.is_dead(){
+            return Err(MountError::DeadMount);
+        }
+        if mount.size < Size::Large{
+            return Err(MountError::TooSmall);
+        }
+        if self.mounts.contains_key(&rider){
+            return Err(MountError::AlreadyRiding);
+        }
+        if self.rider_of(mount_id).is_some(){
+            return Err(MountError::AlreadyRidden);
+        }
+        self.mounts.insert(rider, mount_id);
+        Ok(())
+    }
+
+    // returns the mount the rider got off
+    pub fn dismount(&mut self, rider: ActorId) -> Result<ActorId, MountError>{
+        self.mounts.remove(&rider).ok_or(MountError::NotRiding)
+    }
+
+    // the actor that does the walking, a mounted rider travels at the speed of its mount and tires it instead of itself
+    pub fn walker(&self, rider: ActorId) -> ActorId{
+        self.mount_of(rider).unwrap_or(rider)
+    }
+
+    // moves a party along a route with its mounted members on their mounts, the stamina used is spent by whoever walked,
+    // the journey's stamina used is by walker in the party's order
+    pub fn travel(
+        &self,
+        map: &Map,
+        route: &Route,
+        party: &[ActorId],
+        actors: &mut HashMap<ActorId, Actor>,
+        clock: &mut WorldClock,
+        rng: &mut impl Rng,
+    ) -> Result<Journey, MountError>{
+        let walkers: Vec<ActorId> = party.iter().map(|member| self.walker(*member)).collect();
+        let walking = walkers.iter()
+            .map(|walker| actors.get(walker).ok_or(MountError::UnknownActor(*walker)))
+            .collect::<Result<Vec<&Actor>, MountError>>()?;
+        let journey = travel(map, route, &walking, clock, rng);
+        for (walker, stamina) in walkers.iter().zip(&journey.stamina_used){
+            if let Some(actor) = actors.get_mut(walker){
+                actor.tire(*stamina as f32);
+            }
+        }
+        Ok(journey)
+    }
+
+    // a melee attack made charging on a mount hits harder the better the rider is, an attack made on foot is unchanged
+    pub fn charge(&self, damage: Damage, rider_id: ActorId, rider: &Actor) -> Damage{
+        if self.mount_of(rider_id).is_none(){
+            return damage;
+        }
+        let mut new_damage = damage;
+        new_damage.amount *= 1.0 + MAX_CHARGE_BONUS * rider.get_stats().agility.riding.clamp(0.0, 1.0);
+        new_damage
+    }
+
+    // a hit on a ridden mount can throw its rider off, the harder the hit the likelier, riding makes it less likely
+    // and a mount that dies always does, returns true if the rider was thrown
+    pub fn mount_hit(
+        &mut self,
+        mount_id: ActorId,
+        result: DamageResult,
+        rider_id: ActorId,
+        rider: &mut Actor,
+        rng: &mut impl Rng,
+    ) -> Result<bool, MountError>{
+        if self.mount_of(rider_id) != Some(mount_id){
+            return Err(MountError::NotRiding);
+        }
+        let chance = match result{
+            DamageResult::Killed | DamageResult::Destroyed => 1.0,
+            _ => severity(result) * (1.0 - rider.get_stats().agility.riding.clamp(0.0, 1.0)),
+        };
+        if chance <= 0.0 || rng.gen::<f32>() >= chance{
+            return Ok(false);
+        }
+        self.mounts.remove(&rider_id);
+        rider.add_status(StatusType::Stun, THROWN_STUN);
+        Ok(true)
+    }
+}
+
+
+// riding practiced by staying on a hit mount, or being thrown off it, the harder the hit the more it teaches
+pub fn riding_practice(result: DamageResult, thrown: bool) -> Practice{
+    Practice::new(SubStat::Riding, severity(result), !thrown)
+}
+
+// how likely a hit on a mount is to throw an unskilled rider off
+fn severity(result: DamageResult) -> f32{
+    match result{
+        DamageResult::Killed | DamageResult::Destroyed => 1.0,
+        DamageResult::Damage(amount) => (amount / THROWING_DAMAGE).min(1.0),
+        DamageResult::NoDamage => 0.0,
+    }
+}
+
+
+impl Default for Stables{
+    fn default() -> Stables{
+        Stables::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::actors::actor::Body;
+    use crate::actors::creature::{creature_library, creature_plan};
+    use crate::common::damage::{DamageType, PhysicalDamageType};
+    use crate::common::stats::Stats;
+    use crate::world::world_elements::Biome;
+
+    use super::*;
+
+    const RIDER: ActorId = 1;
+    const MASTER: ActorId = 2;
+    const HORSE: ActorId = 10;
+    const WARHORSE: ActorId = 11;
+
+    fn rider(riding: f32) -> Actor{
+        let mut stats = Stats::new_zero();
+        stats.agility.riding = riding;
+        stats.agility.max_sprint_speed = 5;
+        stats.endurance.base_stamina_used_for_movement = 2;
+        Actor::new(Body::new(vec![], 180, 75), stats)
+    }
+
+    fn horse() -> Creature{
+        let mut stats = Stats::new_zero();
+        stats.agility.max_sprint_speed = 15;
+        stats.endurance.stamina = 9;
+        stats.endurance.base_stamina_used_for_movement = 3;
+        creature_plan(&creature_library(), "Horse").unwrap().spawn(stats)
+    }
+
+    #[test]
+    fn test_mount_and_dismount() {
+        let mut stables = Stables::new();
+        let horse = horse();
+        let wolf = creature_plan(&creature_library(), "Wolf").unwrap().spawn(Stats::new_zero());
+        assert_eq!(stables.mount(RIDER, 12, &wolf), Err(MountError::TooSmall));
+        stables.mount(RIDER, HORSE, &horse).unwrap();
+        assert_eq!(stables.mount_of(RIDER), Some(HORSE));
+        assert_eq!(stables.rider_of(HORSE), Some(RIDER));
+        assert_eq!(stables.mount(MASTER, HORSE, &horse), Err(MountError::AlreadyRidden));
+        assert_eq!(stables.mount(RIDER, WARHORSE, &horse), Err(MountError::AlreadyRiding));
+        assert_eq!(stables.dismount(RIDER), Ok(HORSE));
+        assert_eq!(stables.dismount(RIDER), Err(MountError::NotRiding));
+    }
+
+    #[test]
+    fn test_travel_on_a_mount() {
+        let map = Map::new(4, 1, Biome::Plains, 0);
+        let route = Route{ regions: vec![(0, 0), (1, 0), (2, 0), (3, 0)], base_time: 180 };
+        let mut actors = HashMap::from([(RIDER, rider(0.0)), (HORSE, horse().actor)]);
+        let mut stables = Stables::new();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let on_foot = stables.travel(&map, &route, &[RIDER], &mut actors, &mut WorldClock::at(0, 8, 0), &mut rng).unwrap();
+        assert_eq!(actors[&RIDER].fatigue(), on_foot.stamina_used[0] as f32);
+        assert!(actors[&RIDER].fatigue() > 0.0);
+        actors.get_mut(&RIDER).unwrap().rest(1000.0);
+
+        stables.mount(RIDER, HORSE, &horse()).unwrap();
+        let mounted = stables.travel(&map, &route, &[RIDER], &mut actors, &mut WorldClock::at(0, 8, 0), &mut rng).unwrap();
+        assert!(mounted.travel_time < on_foot.travel_time);
+        assert_eq!(actors[&RIDER].fatigue(), 0.0);
+        assert!(actors[&HORSE].fatigue() > 0.0);
+
+        stables.mount(MASTER, WARHORSE, &horse()).unwrap();
+        let missing = stables.travel(&map, &route, &[RIDER, MASTER], &mut actors, &mut WorldClock::at(0, 8, 0), &mut rng);
+        assert_eq!(missing.err(), Some(MountError::UnknownActor(WARHORSE)));
+    }
+
+    #[test]
+    fn test_charge_needs_a_mount() {
+        let damage = Damage{ damage_type: DamageType::Physical(PhysicalDamageType::Pierce), amount: 10.0 };
+        let mut stables = Stables::new();
+        assert_eq!(stables.charge(damage, RIDER, &rider(1.0)).amount, 10.0);
+        stables.mount(RIDER, HORSE, &horse()).unwrap();
+        assert_eq!(stables.charge(damage, RIDER, &rider(0.0)).amount, 10.0);
+        assert_eq!(stables.charge(damage, RIDER, &rider(0.5)).amount, 15.0);
+        assert_eq!(stables.charge(damage, RIDER, &rider(1.0)).amount, 20.0);
+        assert_eq!(stables.charge(damage, MASTER, &rider(1.0)).amount, 10.0);
+    }
+
+    #[test]
+    fn test_hits_throw_riders() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut stables = Stables::new();
+        let (mut novice, mut master) = (rider(0.0), rider(1.0));
+        stables.mount(RIDER, HORSE, &horse()).unwrap();
+        stables.mount(MASTER, WARHORSE, &horse()).unwrap();
+
+        assert_eq!(stables.mount_hit(HORSE, DamageResult::Damage(THROWING_DAMAGE), MASTER, &mut master, &mut rng), Err(MountError::NotRiding));
+        assert_eq!(stables.mount_hit(HORSE, DamageResult::NoDamage, RIDER, &mut novice, &mut rng), Ok(false));
+        for _ in 0..20{
+            assert_eq!(stables.mount_hit(WARHORSE, DamageResult::Damage(THROWING_DAMAGE), MASTER, &mut master, &mut rng), Ok(false));
+        }
+        assert_eq!(stables.mount_hit(HORSE, DamageResult::Damage(THROWING_DAMAGE), RIDER, &mut novice, &mut rng), Ok(true));
+        assert!(novice.has_status(StatusType::Stun));
+        assert!(!master.has_status(StatusType::Stun));
+        assert_eq!(riding_practice(DamageResult::Damage(THROWING_DAMAGE / 2.0), false), Practice::new(SubStat::Riding, 0.5, true));
+        assert_eq!(riding_practice(DamageResult::NoDamage, false).difficulty, 0.0);
+        assert_eq!(stables.mount_of(RIDER), None);
+
+        assert_eq!(stables.mount_hit(WARHORSE, DamageResult::Killed, MASTER, &mut master, &mut rng), Ok(true));
+        assert_eq!(stables.mount_hit(WARHORSE, DamageResult::Killed, MASTER, &mut master, &mut rng), Err(MountError::NotRiding));
+    }
+}
@@ -1,7 +1,12 @@
+use rand::RngCore;
+
 use crate::actors::actor::Actor;
+use crate::actors::behavior::{decide, Action, Memory, Node, Situation};
+use crate::actors::faction::Faction;
 
 
-enum Role{
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Role{
     Bandit,
     Guard,
     Shopkeeper,
@@ -11,7 +16,30 @@ enum Role{
     Mage,
 }
 
-struct NPC{
-    actor: Actor,
-    role: Role,
-}
\ No newline at end of file
+pub struct NPC{
+    pub actor: Actor,
+    pub role: Role,
+    pub behavior: Node,
+    pub memory: Memory,
+}
+
+
+impl NPC{
+    // uses the default behavior of the role
+    pub fn new(actor: Actor, role: Role) -> NPC{
+        NPC{
+            actor,
+            role,
+            behavior: Node::default_for(&role),
+            memory: Memory::default(),
+        }
+    }
+
+    pub fn faction(&self) -> Faction{
+        self.role.faction()
+    }
+
+    pub fn decide(&mut self, situation: &Situation, rng: &mut dyn RngCore) -> Action{
+        decide(&self.behavior, &self.actor, situation, &mut self.memory, rng)
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use crate::actors::actor::Actor;
+use crate::common::stats::SubStat;
+use crate::world::time::WorldClock;
+
+// experience earned by succeeding at an action of difficulty 1 with average learning
+const BASE_EXPERIENCE: f32 = 0.02;
+
+// most a sub-stat can grow in a day, practicing all day long doesn't make a master overnight
+pub const DAILY_GAIN_CAP: f32 = 0.03;
+
+pub const MAX_SUB_STAT: f32 = 1.0;
+
+
+// an action that used a sub-stat, actions hand these back so the actor can learn from them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Practice{
+    pub sub_stat: SubStat,
+    pub difficulty: f32,
+    pub succeeded: bool,
+}
+
+// what an actor learned by doing things
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Progression{
+    experience: HashMap<SubStat, f32>,      // earned in each sub-stat, before diminishing returns
+    gained_today: HashMap<SubStat, f32>,
+    day: u64,
+}
+
+
+impl Progression{
+    pub fn new() -> Progression{
+        Progression{
+            experience: HashMap::new(),
+            gained_today: HashMap::new(),
+            day: 0,
+        }
+    }
+
+    pub fn experience(&self, sub_stat: SubStat) -> f32{
+        self.experience.get(&sub_stat).copied().unwrap_or(0.0)
+    }
+
+    // the actor used a sub-stat in an action, quick learners earn more, failing teaches as much as the actor
+    // applies itself, a high sub-stat grows slowly, returns how much the sub-stat grew in the actor's base stats
+    pub fn practice(&mut self, actor: &mut Actor, sub_stat: SubStat, difficulty: f32, succeeded: bool, clock: &WorldClock) -> f32{
+        if clock.day() != self.day{
+            self.day = clock.day();
+            self.gained_today.clear();
+        }
+        let intelligence = actor.get_stats().intelligence;
+        let mut experience = BASE_EXPERIENCE * difficulty.max(0.0) * (0.5 + intelligence.learning.clamp(0.0, 1.0));
+        if !succeeded{
+            experience *= 0.5 * intelligence.application.clamp(0.0, 1.0);
+        }
+        *self.experience.entry(sub_stat).or_insert(0.0) += experience;
+
+        let value = actor.base_stats_mut().sub_stat_mut(sub_stat);
+        let gained_today = self.gained_today.entry(sub_stat).or_insert(0.0);
+        let gain = (experience * (MAX_SUB_STAT - *value).max(0.0).powi(2))
+            .min(DAILY_GAIN_CAP - *gained_today)
+            .min(MAX_SUB_STAT - *value)
+            .max(0.0);
+        *value += gain;
+        *gained_today += gain;
+        gain
+    }
+
+    // learns from an action, returns how much the sub-stat grew
+    pub fn learn(&mut self, actor: &mut Actor, practice: Practice, clock: &WorldClock) -> f32{
+        self.practice(actor, practice.sub_stat, practice.difficulty, practice.succeeded, clock)
+    }
+}
+
+
+impl Practice{
+    pub fn new(sub_stat: SubStat, difficulty: f32, succeeded: bool) -> Practice{
+        Practice{
+            sub_stat,
+            difficulty,
+            succeeded,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::actor::Body;
+    use crate::common::stats::Stats;
+
+    use super::*;
+
+    fn actor(learning: f32, application: f32, stealth: f32) -> Actor{
+        let mut stats = Stats::new_zero();
+        stats.intelligence.learning = learning;
+        stats.intelligence.application = application;
+        stats.agility.stealth = stealth;
+        Actor::new(Body::new(vec![], 170, 60), stats)
+    }
+
+    #[test]
+    fn test_practice_grows_base_stats() {
+        let mut progression = Progression::new();
+        let mut thief = actor(0.5, 0.5, 0.0);
+        let gain = progression.practice(&mut thief, SubStat::Stealth, 1.0, true, &WorldClock::new(0));
+        assert!(gain > 0.0);
+        assert_eq!(thief.base_stats().agility.stealth, gain);
+        assert!(progression.experience(SubStat::Stealth) > 0.0);
+        assert_eq!(progression.experience(SubStat::Climbing), 0.0);
+    }
+
+    #[test]
+    fn test_learning_and_application() {
+        let clock = WorldClock::new(0);
+        let gain = |learning: f32, application: f32, succeeded: bool| {
+            Progression::new().practice(&mut actor(learning, application, 0.0), SubStat::Stealth, 1.0, succeeded, &clock)
+        };
+        assert!(gain(1.0, 0.0, true) > gain(0.0, 0.0, true));
+        assert!(gain(0.5, 0.5, false) < gain(0.5, 0.5, true));
+        assert!(gain(0.5, 1.0, false) > gain(0.5, 0.0, false));
+        assert_eq!(gain(0.5, 0.0, false), 0.0);
+    }
+
+    #[test]
+    fn test_diminishing_returns_and_caps() {
+        let clock = WorldClock::new(0);
+        let novice = Progression::new().practice(&mut actor(0.5, 0.5, 0.1), SubStat::Stealth, 1.0, true, &clock);
+        let expert = Progression::new().practice(&mut actor(0.5, 0.5, 0.9), SubStat::Stealth, 1.0, true, &clock);
+        assert!(expert < novice);
+
+        let mut progression = Progression::new();
+        let mut thief = actor(1.0, 1.0, 0.0);
+        let total: f32 = (0..100).map(|_| progression.practice(&mut thief, SubStat::Stealth, 5.0, true, &clock)).sum();
+        assert!((total - DAILY_GAIN_CAP).abs() < 1e-6);
+        assert!(progression.practice(&mut thief, SubStat::Stealth, 5.0, true, &WorldClock::at(1, 0, 0)) > 0.0);
+
+        let mut master = actor(1.0, 1.0, MAX_SUB_STAT);
+        assert_eq!(Progression::new().practice(&mut master, SubStat::Stealth, 5.0, true, &clock), 0.0);
+        assert_eq!(master.base_stats().agility.stealth, MAX_SUB_STAT);
+    }
+}
@@ -0,0 +1,276 @@
+use crate::actors::npc::{Role, NPC};
+use crate::common::position::Coordinates;
+use crate::common::status::StatusType;
+use crate::world::buildings::Building;
+use crate::world::map::Location;
+use crate::world::time::{WorldClock, TICKS_PER_DAY, TICKS_PER_HOUR};
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Activity{
+    Work,
+    Trade,                          // keeps the shop open
+    Patrol(Vec<Vec<Coordinates>>),  // routes taken in turns, a different one every day
+    Rest,
+    Sleep,
+}
+
+// somewhere on a local map
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Place{
+    pub location: Location,
+    pub position: Coordinates,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleEntry{
+    pub start_hour: u64,
+    pub activity: Activity,
+    pub place: Place,
+}
+
+// what an npc does every day, each entry lasts until the next one starts
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Schedule{
+    entries: Vec<ScheduleEntry>,
+    pub shift: u64,     // offsets patrol rotations so guards don't all walk the same route
+}
+
+// what following the schedule asks of the npc
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Routine{
+    Travel(Location),   // it has to go to another location first
+    Settled(Activity),
+}
+
+
+impl Place{
+    pub fn new(location: Location, position: Coordinates) -> Place{
+        Place{ location, position }
+    }
+
+    // the first tile inside a building, or its door for buildings with no inside
+    pub fn inside(location: Location, building: &Building) -> Place{
+        let position = building.interior().first().copied()
+            .or(building.doors.first().copied())
+            .unwrap_or(building.origin);
+        Place{ location, position }
+    }
+}
+
+
+impl Schedule{
+    pub fn new() -> Schedule{
+        Schedule{
+            entries: Vec::new(),
+            shift: 0,
+        }
+    }
+
+    // entries are kept sorted by start hour, one starting at the same hour as another replaces it
+    pub fn add(&mut self, start_hour: u64, activity: Activity, place: Place){
+        self.entries.retain(|entry| entry.start_hour != start_hour);
+        let index = self.entries.partition_point(|entry| entry.start_hour < start_hour);
+        self.entries.insert(index, ScheduleEntry{ start_hour, activity, place });
+    }
+
+    // the usual day of a role, work is where the npc spends its working hours and home where it sleeps
+    pub fn for_role(role: &Role, home: Place, work: Place, patrols: Vec<Vec<Coordinates>>) -> Schedule{
+        let mut schedule = Schedule::new();
+        match role{
+            Role::Farmer => {
+                schedule.add(6, Activity::Work, work);
+                schedule.add(20, Activity::Sleep, home);
+            }
+            Role::Shopkeeper | Role::Blacksmith => {
+                schedule.add(8, Activity::Trade, work);
+                schedule.add(18, Activity::Rest, home.clone());
+                schedule.add(22, Activity::Sleep, home);
+            }
+            Role::Guard => {
+                schedule.add(6, Activity::Patrol(patrols), work);
+                schedule.add(18, Activity::Rest, home.clone());
+                schedule.add(22, Activity::Sleep, home);
+            }
+            // bandits sleep through the day and wait for travellers at night
+            Role::Bandit => {
+                schedule.add(8, Activity::Sleep, home);
+                schedule.add(18, Activity::Patrol(patrols), work);
+            }
+            Role::Noble | Role::Mage => {
+                schedule.add(9, Activity::Rest, work);
+                schedule.add(23, Activity::Sleep, home);
+            }
+        }
+        schedule
+    }
+
+    pub fn entries(&self) -> &[ScheduleEntry]{
+        &self.entries
+    }
+
+    // the entry going on, before the first entry of the day the last one of the day before is still going on
+    pub fn current(&self, clock: &WorldClock) -> Option<&ScheduleEntry>{
+        let hour = clock.ticks() % TICKS_PER_DAY / TICKS_PER_HOUR;
+        self.entries.iter().rev().find(|entry| entry.start_hour <= hour).or(self.entries.last())
+    }
+
+    // ticks left before the next entry starts
+    pub fn ticks_until_next(&self, clock: &WorldClock) -> u64{
+        let time_of_day = clock.ticks() % TICKS_PER_DAY;
+        let next = self.entries.iter()
+            .map(|entry| entry.start_hour * TICKS_PER_HOUR)
+            .find(|start| *start > time_of_day)
+            .or(self.entries.first().map(|entry| entry.start_hour * TICKS_PER_HOUR + TICKS_PER_DAY));
+        next.map_or(TICKS_PER_DAY, |start| start - time_of_day)
+    }
+
+    // sets the npc's goals for the current entry, its routine, post and patrol route, and puts it to sleep once in bed
+    pub fn follow(&self, npc: &mut NPC, location: &Location, position: Coordinates, clock: &WorldClock) -> Option<Routine>{
+        let entry = self.current(clock)?;
+        if !matches!(entry.activity, Activity::Patrol(_)) || entry.place.location != *location{
+            npc.memory.patrol_route.clear();
+            npc.memory.patrol_index = 0;
+        }
+        if entry.place.location != *location{
+            npc.memory.post = None;
+            npc.memory.routine = None;
+            return Some(Routine::Travel(entry.place.location.clone()));
+        }
+        npc.memory.post = Some(entry.place.position);
+        npc.memory.routine = Some(entry.activity.clone());
+        match &entry.activity{
+            Activity::Sleep if position == entry.place.position && !npc.actor.has_status(StatusType::Sleep) => {
+                let duration = self.ticks_until_next(clock) as u32;
+                npc.actor.add_status(StatusType::Sleep, duration);
+            }
+            Activity::Patrol(routes) if !routes.is_empty() => {
+                let route = &routes[((clock.day() + self.shift) % routes.len() as u64) as usize];
+                if npc.memory.patrol_route != *route{
+                    npc.memory.patrol_route = route.clone();
+                    npc.memory.patrol_index = 0;
+                }
+            }
+            _ => {}
+        }
+        Some(Routine::Settled(entry.activity.clone()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::actors::actor::{Actor, Body};
+    use crate::actors::behavior::{Action, Situation};
+    use crate::common::stats::Stats;
+    use crate::world::buildings::place_building;
+    use crate::world::map::LocalMap;
+    use crate::world::world_elements::{BuildingType, TileType};
+
+    use super::*;
+
+    fn village() -> Location{
+        Location::new(String::from("Village"), String::from("A few houses"), 1, 1)
+    }
+
+    fn npc(role: Role) -> NPC{
+        NPC::new(Actor::new(Body::new(vec![], 180, 70), Stats::new_zero()), role)
+    }
+
+    fn farmer_schedule() -> (Schedule, Place, Place){
+        let mut map = LocalMap::new(20, 20, TileType::Grass);
+        let house = place_building(&mut map, BuildingType::House, Coordinates::new(1, 1), 6, 5).unwrap();
+        let (home, field) = (Place::inside(village(), &house), Place::new(village(), Coordinates::new(15, 15)));
+        (Schedule::for_role(&Role::Farmer, home.clone(), field.clone(), vec![]), home, field)
+    }
+
+    #[test]
+    fn test_current_entry() {
+        let (schedule, home, field) = farmer_schedule();
+        assert_eq!(home.position, Coordinates::new(2, 2));
+        assert_eq!(schedule.current(&WorldClock::at(0, 12, 0)).unwrap().place, field);
+        assert_eq!(schedule.current(&WorldClock::at(0, 21, 0)).unwrap().activity, Activity::Sleep);
+        assert_eq!(schedule.current(&WorldClock::at(1, 3, 0)).unwrap().activity, Activity::Sleep);
+        assert_eq!(schedule.ticks_until_next(&WorldClock::at(0, 5, 30)), 30);
+        assert_eq!(schedule.ticks_until_next(&WorldClock::at(0, 20, 0)), 10 * TICKS_PER_HOUR);
+        assert!(Schedule::new().current(&WorldClock::at(0, 12, 0)).is_none());
+    }
+
+    #[test]
+    fn test_farmer_day() {
+        let (schedule, home, field) = farmer_schedule();
+        let mut farmer = npc(Role::Farmer);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let morning = WorldClock::at(0, 7, 0);
+        assert_eq!(schedule.follow(&mut farmer, &village(), home.position, &morning), Some(Routine::Settled(Activity::Work)));
+        let situation = Situation::new(home.position, morning);
+        assert_eq!(farmer.decide(&situation, &mut rng), Action::MoveTo(field.position));
+        let situation = Situation::new(field.position, morning);
+        assert_eq!(farmer.decide(&situation, &mut rng), Action::Work);
+
+        let evening = WorldClock::at(0, 20, 30);
+        schedule.follow(&mut farmer, &village(), field.position, &evening);
+        assert!(!farmer.actor.has_status(StatusType::Sleep));
+        schedule.follow(&mut farmer, &village(), home.position, &evening);
+        assert!(farmer.actor.has_status(StatusType::Sleep));
+        assert_eq!(farmer.decide(&Situation::new(home.position, evening), &mut rng), Action::Sleep);
+
+        let elsewhere = Location::new(String::from("Town"), String::from("Far away"), 5, 5);
+        assert_eq!(schedule.follow(&mut farmer, &elsewhere, home.position, &morning), Some(Routine::Travel(village())));
+    }
+
+    #[test]
+    fn test_guards_rotate_patrols() {
+        let routes = vec![vec![Coordinates::new(0, 0), Coordinates::new(5, 0)], vec![Coordinates::new(0, 9), Coordinates::new(5, 9)]];
+        let barracks = Place::new(village(), Coordinates::new(3, 3));
+        let mut schedule = Schedule::for_role(&Role::Guard, barracks.clone(), barracks, routes.clone());
+        let (mut first, mut second) = (npc(Role::Guard), npc(Role::Guard));
+        let day = WorldClock::at(0, 10, 0);
+        schedule.follow(&mut first, &village(), Coordinates::new(3, 3), &day);
+        schedule.shift = 1;
+        schedule.follow(&mut second, &village(), Coordinates::new(3, 3), &day);
+        assert_eq!(first.memory.patrol_route, routes[0]);
+        assert_eq!(second.memory.patrol_route, routes[1]);
+
+        schedule.follow(&mut second, &village(), Coordinates::new(3, 3), &WorldClock::at(1, 10, 0));
+        assert_eq!(second.memory.patrol_route, routes[0]);
+    }
+
+    #[test]
+    fn test_guard_goes_home_to_sleep() {
+        let routes = vec![vec![Coordinates::new(0, 0), Coordinates::new(9, 0)]];
+        let (home, barracks) = (Place::new(village(), Coordinates::new(3, 3)), Place::new(village(), Coordinates::new(9, 9)));
+        let schedule = Schedule::for_role(&Role::Guard, home.clone(), barracks, routes);
+        let mut guard = npc(Role::Guard);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let mut clock = WorldClock::at(0, 17, 0);
+        let mut position = Coordinates::new(9, 0);
+        schedule.follow(&mut guard, &village(), position, &clock);
+        assert!(matches!(guard.decide(&Situation::new(position, clock), &mut rng), Action::MoveTo(_)));
+        assert!(!guard.memory.patrol_route.is_empty());
+
+        clock = WorldClock::at(0, 18, 0);
+        while clock.ticks() < WorldClock::at(1, 6, 0).ticks(){
+            schedule.follow(&mut guard, &village(), position, &clock);
+            assert!(guard.memory.patrol_route.is_empty());
+            match guard.decide(&Situation::new(position, clock), &mut rng){
+                Action::MoveTo(target) => {
+                    assert_eq!(target, home.position);
+                    position = target;
+                }
+                action => assert!(matches!(action, Action::Idle | Action::Sleep)),
+            }
+            guard.actor.tick_statuses();
+            clock.advance(1);
+            if clock.ticks() == WorldClock::at(1, 5, 0).ticks(){
+                assert_eq!(position, home.position);
+                assert!(guard.actor.has_status(StatusType::Sleep));
+            }
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use rand::Rng;
+
+use crate::actors::actor::{Actor, ActorId, Control};
+use crate::actors::detection::{Awareness, DetectionTracker};
+use crate::actors::faction::{Deed, Faction, Factions, Relation};
+use crate::actors::progression::Practice;
+use crate::common::stats::SubStat;
+
+// chances of lifting an item are kept between these, nobody is a sure mark or completely untouchable
+const MIN_THEFT_CHANCE: f32 = 0.05;
+const MAX_THEFT_CHANCE: f32 = 0.95;
+
+// every kg of the item makes it this much harder to take unnoticed
+const WEIGHT_PENALTY: f32 = 0.05;
+
+// how much the victim's awareness of the thief changes the chance
+const UNAWARE_BONUS: f32 = 0.2;
+const SUSPICIOUS_PENALTY: f32 = 0.2;
+const ASLEEP_BONUS: f32 = 0.4;
+
+// chance of a failed attempt going unnoticed is kept between these
+const MIN_NOTICE_CHANCE: f32 = 0.1;
+const MAX_NOTICE_CHANCE: f32 = 0.95;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TheftError{
+    NotCarried,     // the victim doesn't have the item
+    Watched,        // the victim has its eyes on the thief, there's no way to try
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theft{
+    Stolen,
+    Fumbled,        // nothing was taken, the victim only got suspicious
+    Caught,         // nothing was taken and the victim noticed the attempt
+}
+
+// how an attempt went and the pickpocketing the thief practiced
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attempt{
+    pub theft: Theft,
+    pub practice: Practice,
+}
+
+pub struct Thief<'a>{
+    pub id: ActorId,
+    pub actor: &'a mut Actor,
+}
+
+pub struct Victim<'a>{
+    pub id: ActorId,
+    pub actor: &'a mut Actor,
+    pub faction: Option<Faction>,
+}
+
+
+// chance of the thief taking the item from the victim without being noticed, the thief's pickpocketing
+// against the victim's eyes and ears, a victim that is asleep or knocked out barely notices anything
+pub fn theft_chance(thief: &Actor, victim: &Actor, awareness: Awareness, item_weight: u32) -> f32{
+    let pickpocketing = thief.get_stats().agility.pickpocting;
+    let perception = victim.get_stats().perception;
+    let vigilance = match awareness{
+        Awareness::Unaware => -UNAWARE_BONUS,
+        Awareness::Suspicious => SUSPICIOUS_PENALTY,
+        Awareness::Detected => return 0.0,
+    };
+    let helpless = match victim.control(){
+        Some(Control::Asleep | Control::Stunned) => ASLEEP_BONUS,
+        _ => 0.0,
+    };
+    let chance = 0.5 + pickpocketing - (perception.vision + perception.hearing) / 2.0 - vigilance + helpless
+        - item_weight as f32 * WEIGHT_PENALTY;
+    chance.clamp(MIN_THEFT_CHANCE, MAX_THEFT_CHANCE)
+}
+
+
+// chance of the victim noticing a failed attempt, sharp senses and suspicion make it likelier, sleep much less likely
+pub fn notice_chance(victim: &Actor, awareness: Awareness) -> f32{
+    let perception = victim.get_stats().perception;
+    let vigilance = match awareness{
+        Awareness::Unaware => 0.0,
+        Awareness::Suspicious => SUSPICIOUS_PENALTY,
+        Awareness::Detected => return 1.0,
+    };
+    let helpless = match victim.control(){
+        Some(Control::Asleep | Control::Stunned) => ASLEEP_BONUS,
+        _ => 0.0,
+    };
+    (0.3 + (perception.vision + perception.hearing) / 2.0 + vigilance - helpless).clamp(MIN_NOTICE_CHANCE, MAX_NOTICE_CHANCE)
+}
+
+
+// tries to move an item from the victim's inventory to the thief's, a failed attempt can still go unnoticed,
+// a thief that is caught loses reputation with the victim's faction and, if the watch protects it, becomes an enemy of the watch,
+// the less likely the theft was the more it teaches
+pub fn steal(
+    thief: Thief,
+    victim: Victim,
+    item_id: u32,
+    tracker: &mut DetectionTracker,
+    factions: &mut Factions,
+    rng: &mut impl Rng,
+) -> Result<Attempt, TheftError>{
+    let item_weight = victim.actor.inventory().iter().find(|object| object.id == item_id).ok_or(TheftError::NotCarried)?.weight;
+    let awareness = tracker.awareness(victim.id, thief.id);
+    if awareness == Awareness::Detected{
+        return Err(TheftError::Watched);
+    }
+
+    let chance = theft_chance(thief.actor, victim.actor, awareness, item_weight);
+    let attempt = |theft| Attempt{ theft, practice: Practice::new(SubStat::Pickpocting, 1.0 - chance, theft == Theft::Stolen) };
+    if rng.gen::<f32>() < chance{
+        let item = victim.actor.remove_from_inventory(item_id).ok_or(TheftError::NotCarried)?;
+        thief.actor.add_to_inventory(item);
+        return Ok(attempt(Theft::Stolen));
+    }
+
+    if rng.gen::<f32>() >= notice_chance(victim.actor, awareness){
+        tracker.suspect(victim.id, thief.id);
+        return Ok(attempt(Theft::Fumbled));
+    }
+    tracker.alert(victim.id, thief.id);
+    if let Some(faction) = victim.faction{
+        factions.record(thief.id, Deed::Stole, faction);
+        if factions.relation(Faction::TownWatch, faction) == Relation::Friendly{
+            factions.turn_hostile(thief.id, Faction::TownWatch);
+        }
+    }
+    Ok(attempt(Theft::Caught))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::actors::actor::Body;
+    use crate::actors::trade::{bread, count_items, BREAD_ID};
+    use crate::common::stats::Stats;
+    use crate::common::status::StatusType;
+
+    use super::*;
+
+    const THIEF: ActorId = 1;
+    const VICTIM: ActorId = 2;
+
+    fn actor(pickpocketing: f32, perception: f32) -> Actor{
+        let mut stats = Stats::new_zero();
+        stats.agility.pickpocting = pickpocketing;
+        stats.perception.vision = perception;
+        stats.perception.hearing = perception;
+        let mut actor = Actor::new(Body::new(vec![], 175, 70), stats);
+        actor.add_to_inventory(bread());
+        actor
+    }
+
+    fn try_stealing(
+        thief: &mut Actor,
+        victim: &mut Actor,
+        faction: Option<Faction>,
+        tracker: &mut DetectionTracker,
+        factions: &mut Factions,
+        rng: &mut StdRng,
+    ) -> Result<Theft, TheftError>{
+        let (thief, victim) = (Thief{ id: THIEF, actor: thief }, Victim{ id: VICTIM, actor: victim, faction });
+        steal(thief, victim, BREAD_ID, tracker, factions, rng).map(|attempt| attempt.theft)
+    }
+
+    #[test]
+    fn test_theft_chance() {
+        let (thief, victim) = (actor(0.5, 0.0), actor(0.0, 0.5));
+        let unaware = theft_chance(&thief, &victim, Awareness::Unaware, 0);
+        assert!(unaware > theft_chance(&thief, &victim, Awareness::Suspicious, 0));
+        assert!(unaware > theft_chance(&thief, &victim, Awareness::Unaware, 5));
+        assert!(unaware > theft_chance(&actor(0.1, 0.0), &victim, Awareness::Unaware, 0));
+        assert_eq!(theft_chance(&thief, &victim, Awareness::Detected, 0), 0.0);
+
+        let mut sleeping = actor(0.0, 0.3);
+        let awake = theft_chance(&thief, &sleeping, Awareness::Suspicious, 0);
+        let awake_notice = notice_chance(&sleeping, Awareness::Suspicious);
+        assert!(awake_notice > notice_chance(&sleeping, Awareness::Unaware));
+        assert!(notice_chance(&sleeping, Awareness::Unaware) > notice_chance(&actor(0.0, 0.0), Awareness::Unaware));
+        sleeping.add_status(StatusType::Sleep, 100);
+        assert!(theft_chance(&thief, &sleeping, Awareness::Suspicious, 0) > awake);
+        assert!(notice_chance(&sleeping, Awareness::Suspicious) < awake_notice);
+    }
+
+    #[test]
+    fn test_steal_moves_items() {
+        let (mut thief, mut victim) = (actor(1.0, 0.0), actor(0.0, 0.0));
+        let (mut tracker, mut factions) = (DetectionTracker::new(), Factions::new());
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut stolen = 0;
+        while count_items(&victim, BREAD_ID) > 0{
+            let theft = try_stealing(&mut thief, &mut victim, Some(Faction::Merchants), &mut tracker, &mut factions, &mut rng);
+            assert_eq!(theft, Ok(Theft::Stolen));
+            stolen += 1;
+        }
+        assert_eq!((stolen, count_items(&thief, BREAD_ID)), (1, 2));
+        assert_eq!(try_stealing(&mut thief, &mut victim, None, &mut tracker, &mut factions, &mut rng), Err(TheftError::NotCarried));
+        assert_eq!(factions.reputation(THIEF, Faction::Merchants), 0);
+    }
+
+    #[test]
+    fn test_theft_practices_pickpocketing() {
+        let (mut thief, mut victim) = (actor(0.5, 0.0), actor(0.0, 0.5));
+        let chance = theft_chance(&thief, &victim, Awareness::Unaware, bread().weight);
+        let (mut tracker, mut factions) = (DetectionTracker::new(), Factions::new());
+        let (thief, victim) = (Thief{ id: THIEF, actor: &mut thief }, Victim{ id: VICTIM, actor: &mut victim, faction: None });
+        let attempt = steal(thief, victim, BREAD_ID, &mut tracker, &mut factions, &mut StdRng::seed_from_u64(0)).unwrap();
+        assert_eq!(attempt.practice, Practice::new(SubStat::Pickpocting, 1.0 - chance, attempt.theft == Theft::Stolen));
+    }
+
+    #[test]
+    fn test_fumbled_theft_makes_the_victim_suspicious() {
+        let (mut thief, mut victim) = (actor(0.0, 0.0), actor(0.0, 0.0));
+        victim.add_status(StatusType::Sleep, 1000);
+        let (mut tracker, mut factions) = (DetectionTracker::new(), Factions::new());
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut result = Ok(Theft::Stolen);
+        while result == Ok(Theft::Stolen){
+            victim.add_to_inventory(bread());
+            result = try_stealing(&mut thief, &mut victim, Some(Faction::Merchants), &mut tracker, &mut factions, &mut rng);
+        }
+        assert_eq!(result, Ok(Theft::Fumbled));
+        assert_eq!(tracker.awareness(VICTIM, THIEF), Awareness::Suspicious);
+        assert_eq!(factions.reputation(THIEF, Faction::Merchants), 0);
+    }
+
+    #[test]
+    fn test_caught_thief_becomes_wanted() {
+        let (mut thief, mut victim) = (actor(0.0, 0.0), actor(0.0, 1.0));
+        let (mut tracker, mut factions) = (DetectionTracker::new(), Factions::new());
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut result = Ok(Theft::Stolen);
+        while result != Ok(Theft::Caught){
+            victim.add_to_inventory(bread());
+            result = try_stealing(&mut thief, &mut victim, Some(Faction::Merchants), &mut tracker, &mut factions, &mut rng);
+        }
+        assert_eq!(tracker.awareness(VICTIM, THIEF), Awareness::Detected);
+        assert!(factions.reputation(THIEF, Faction::Merchants) < 0);
+        assert_eq!(factions.attitude(Faction::TownWatch, THIEF, None), Relation::Hostile);
+        assert_eq!(
+            try_stealing(&mut thief, &mut victim, Some(Faction::Merchants), &mut tracker, &mut factions, &mut rng),
+            Err(TheftError::Watched),
+        );
+    }
+
+    #[test]
+    fn test_stealing_from_outlaws_does_not_bother_the_watch() {
+        let mut factions = Factions::new();
+        let (mut thief, mut victim) = (actor(0.0, 0.0), actor(0.0, 1.0));
+        let mut tracker = DetectionTracker::new();
+        let mut rng = StdRng::seed_from_u64(4);
+        while try_stealing(&mut thief, &mut victim, Some(Faction::Outlaws), &mut tracker, &mut factions, &mut rng) != Ok(Theft::Caught){
+            victim.add_to_inventory(bread());
+        }
+        assert!(factions.reputation(THIEF, Faction::Outlaws) < 0);
+        assert_ne!(factions.attitude(Faction::TownWatch, THIEF, None), Relation::Hostile);
+    }
+}
@@ -0,0 +1,388 @@
+use crate::actors::actor::{Actor, ActorId};
+use crate::actors::faction::{Deed, Factions, Relation, MAX_REPUTATION};
+use crate::actors::npc::Role;
+use crate::objects::object::{Object, MAX_CONDITION};
+use crate::world::harvesting::{LOG_ID, STONE_ID};
+use crate::world::time::{WorldClock, TICKS_PER_DAY};
+
+// ids of the goods sold in shops
+pub const BREAD_ID: u32 = 1101;
+pub const ROPE_ID: u32 = 1102;
+pub const TORCH_ID: u32 = 1103;
+pub const SWORD_ID: u32 = 1104;
+pub const AXE_ID: u32 = 1105;
+pub const HAMMER_ID: u32 = 1106;
+
+// a merchant asks this much more than an item is worth and pays this much of its worth,
+// persuasion and reputation move both towards the item's worth, reaching it at the best of both
+const BUY_MARKUP: f32 = 1.5;
+const SELL_DISCOUNT: f32 = 0.5;
+const PERSUASION_WEIGHT: f32 = 0.25;
+const REPUTATION_WEIGHT: f32 = 0.25;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeError{
+    Refused,            // the merchant's faction is hostile to the customer
+    NotInStock,
+    NotOwned,           // the customer doesn't have the item
+    NotEnoughGold,
+    NotOffered,         // this shop doesn't do that
+    NothingToRepair,
+    MissingMaterials,
+}
+
+// materials and a fee turned into a new item by a craftsman
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recipe{
+    pub materials: Vec<(u32, u32)>,     // item id and how many are used
+    pub fee: u32,
+    pub output: Object,
+}
+
+// the business of a merchant, goods are kept in the merchant's inventory and coins in its purse
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shop{
+    pub owner: ActorId,
+    pub role: Role,
+    pub wares: Vec<Object>,     // what the shop has after restocking
+    pub gold: u32,              // coins the merchant has after restocking
+    pub recipes: Vec<Recipe>,
+    pub restock_interval: u64,
+    last_restock: u64,
+}
+
+
+pub fn bread() -> Object{
+    goods(BREAD_ID, "Bread", "A loaf of bread", 1, 2, 2)
+}
+
+pub fn rope() -> Object{
+    goods(ROPE_ID, "Rope", "Ten meters of rope", 3, 4, 5)
+}
+
+pub fn torch() -> Object{
+    goods(TORCH_ID, "Torch", "Burns for a few hours", 2, 2, 3)
+}
+
+pub fn sword() -> Object{
+    goods(SWORD_ID, "Sword", "A plain iron sword", 8, 5, 40)
+}
+
+pub fn axe() -> Object{
+    goods(AXE_ID, "Axe", "Good for chopping trees and skulls", 10, 6, 25)
+}
+
+pub fn hammer() -> Object{
+    goods(HAMMER_ID, "Hammer", "Breaks rocks", 6, 3, 15)
+}
+
+fn goods(id: u32, name: &str, description: &str, weight: u32, volume: u32, value: u32) -> Object{
+    Object::new(id, String::from(name), String::from(description), weight, volume).with_value(value)
+}
+
+
+pub fn count_items(actor: &Actor, id: u32) -> u32{
+    actor.inventory().iter().filter(|object| object.id == id).count() as u32
+}
+
+// moves coins from an actor to another, nothing moves if the payer can't afford it
+pub fn pay(payer: &mut Actor, payee: &mut Actor, amount: u32) -> Result<(), TradeError>{
+    if !payer.take_gold(amount){
+        return Err(TradeError::NotEnoughGold);
+    }
+    payee.add_gold(amount);
+    Ok(())
+}
+
+
+// what the work of bringing an item back to perfect condition is worth, the more worn the item the more work
+pub fn labor_worth(item: &Object) -> u32{
+    item.value * MAX_CONDITION.saturating_sub(item.condition) / MAX_CONDITION
+}
+
+
+impl Shop{
+    pub fn new(owner: ActorId, role: Role, wares: Vec<Object>, gold: u32, recipes: Vec<Recipe>, clock: &WorldClock) -> Shop{
+        Shop{
+            owner,
+            role,
+            wares,
+            gold,
+            recipes,
+            restock_interval: TICKS_PER_DAY,
+            last_restock: clock.ticks(),
+        }
+    }
+
+    // the usual goods of a role, None for roles that don't run a shop
+    pub fn for_role(owner: ActorId, role: Role, clock: &WorldClock) -> Option<Shop>{
+        match role{
+            Role::Shopkeeper => Some(Shop::new(owner, role, vec![bread(), bread(), rope(), torch()], 50, vec![], clock)),
+            Role::Blacksmith => Some(Shop::new(owner, role, vec![sword(), axe()], 100, vec![
+                Recipe{ materials: vec![(LOG_ID, 1), (STONE_ID, 2)], fee: 5, output: hammer() },
+                Recipe{ materials: vec![(LOG_ID, 1), (STONE_ID, 1)], fee: 10, output: axe() },
+            ], clock)),
+            _ => None,
+        }
+    }
+
+    // how much the merchant likes the customer, 0 with no persuasion and no reputation, up to 0.5 at the best of both
+    fn goodwill(&self, customer: &Actor, customer_id: ActorId, factions: &Factions) -> f32{
+        let persuasion = customer.get_stats().charisma.persuasion.clamp(0.0, 1.0);
+        let reputation = factions.reputation(customer_id, self.role.faction()) as f32 / MAX_REPUTATION as f32;
+        PERSUASION_WEIGHT * persuasion + REPUTATION_WEIGHT * reputation
+    }
+
+    // what the customer pays for something worth that much, never less than its worth
+    fn asking_price(&self, worth: u32, customer: &Actor, customer_id: ActorId, factions: &Factions) -> u32{
+        let factor = (BUY_MARKUP - self.goodwill(customer, customer_id, factions)).max(1.0);
+        (worth as f32 * factor).ceil() as u32
+    }
+
+    // what the customer pays for an item, never less than what the merchant would pay for it
+    pub fn buy_price(&self, item: &Object, customer: &Actor, customer_id: ActorId, factions: &Factions) -> u32{
+        self.asking_price(item.worth(), customer, customer_id, factions)
+    }
+
+    // what the merchant pays the customer for an item
+    pub fn sell_price(&self, item: &Object, customer: &Actor, customer_id: ActorId, factions: &Factions) -> u32{
+        let factor = (SELL_DISCOUNT + self.goodwill(customer, customer_id, factions)).clamp(0.0, 1.0);
+        (item.worth() as f32 * factor).floor() as u32
+    }
+
+    fn check_welcome(&self, customer_id: ActorId, factions: &Factions) -> Result<(), TradeError>{
+        if factions.attitude(self.role.faction(), customer_id, None) == Relation::Hostile{
+            return Err(TradeError::Refused);
+        }
+        Ok(())
+    }
+
+    // the customer buys an item from the merchant's inventory, returns the price paid
+    pub fn buy(
+        &self,
+        merchant: &mut Actor,
+        customer: &mut Actor,
+        customer_id: ActorId,
+        item_id: u32,
+        factions: &mut Factions,
+    ) -> Result<u32, TradeError>{
+        self.check_welcome(customer_id, factions)?;
+        let item = merchant.inventory().iter().find(|object| object.id == item_id).ok_or(TradeError::NotInStock)?;
+        let price = self.buy_price(item, customer, customer_id, factions);
+        pay(customer, merchant, price)?;
+        let item = merchant.remove_from_inventory(item_id).ok_or(TradeError::NotInStock)?;
+        customer.add_to_inventory(item);
+        factions.record(customer_id, Deed::Traded, self.role.faction());
+        Ok(price)
+    }
+
+    // the customer sells an item to the merchant, returns the price received
+    pub fn sell(
+        &self,
+        merchant: &mut Actor,
+        customer: &mut Actor,
+        customer_id: ActorId,
+        item_id: u32,
+        factions: &mut Factions,
+    ) -> Result<u32, TradeError>{
+        self.check_welcome(customer_id, factions)?;
+        let item = customer.inventory().iter().find(|object| object.id == item_id).ok_or(TradeError::NotOwned)?;
+        let price = self.sell_price(item, customer, customer_id, factions);
+        pay(merchant, customer, price)?;
+        let item = customer.remove_from_inventory(item_id).ok_or(TradeError::NotOwned)?;
+        merchant.add_to_inventory(item);
+        factions.record(customer_id, Deed::Traded, self.role.faction());
+        Ok(price)
+    }
+
+    // brings the wares and the gold back to what the shop starts with once the restock interval has passed,
+    // returns true if it restocked
+    pub fn restock(&mut self, merchant: &mut Actor, clock: &WorldClock) -> bool{
+        if clock.ticks() < self.last_restock + self.restock_interval{
+            return false;
+        }
+        self.last_restock = clock.ticks();
+        for (index, ware) in self.wares.iter().enumerate(){
+            let wanted = self.wares[..=index].iter().filter(|other| other.id == ware.id).count() as u32;
+            if count_items(merchant, ware.id) < wanted{
+                merchant.add_to_inventory(ware.clone());
+            }
+        }
+        merchant.add_gold(self.gold.saturating_sub(merchant.gold()));
+        true
+    }
+
+    // what a blacksmith asks to bring an item back to perfect condition
+    pub fn repair_cost(&self, item: &Object, customer: &Actor, customer_id: ActorId, factions: &Factions) -> u32{
+        self.asking_price(labor_worth(item), customer, customer_id, factions).max(1)
+    }
+
+    // a blacksmith repairs an item in the customer's inventory, returns the price paid
+    pub fn repair(
+        &self,
+        merchant: &mut Actor,
+        customer: &mut Actor,
+        customer_id: ActorId,
+        item_id: u32,
+        factions: &mut Factions,
+    ) -> Result<u32, TradeError>{
+        if self.role != Role::Blacksmith{
+            return Err(TradeError::NotOffered);
+        }
+        self.check_welcome(customer_id, factions)?;
+        let mut item = customer.remove_from_inventory(item_id).ok_or(TradeError::NotOwned)?;
+        let result = if item.condition >= MAX_CONDITION{
+            Err(TradeError::NothingToRepair)
+        }
+        else{
+            let price = self.repair_cost(&item, customer, customer_id, factions);
+            pay(customer, merchant, price).map(|_| price)
+        };
+        if result.is_ok(){
+            item.condition = MAX_CONDITION;
+            factions.record(customer_id, Deed::Traded, self.role.faction());
+        }
+        customer.add_to_inventory(item);
+        result
+    }
+
+    // crafts one of the shop's recipes out of the customer's materials, returns the fee paid
+    pub fn craft(
+        &self,
+        merchant: &mut Actor,
+        customer: &mut Actor,
+        customer_id: ActorId,
+        recipe: usize,
+        factions: &mut Factions,
+    ) -> Result<u32, TradeError>{
+        self.check_welcome(customer_id, factions)?;
+        let recipe = self.recipes.get(recipe).ok_or(TradeError::NotOffered)?;
+        if recipe.materials.iter().any(|(id, amount)| count_items(customer, *id) < *amount){
+            return Err(TradeError::MissingMaterials);
+        }
+        pay(customer, merchant, recipe.fee)?;
+        for (id, amount) in &recipe.materials{
+            for _ in 0..*amount{
+                customer.remove_from_inventory(*id);
+            }
+        }
+        customer.add_to_inventory(recipe.output.clone());
+        factions.record(customer_id, Deed::Traded, self.role.faction());
+        Ok(recipe.fee)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::actor::Body;
+    use crate::actors::faction::Faction;
+    use crate::common::stats::Stats;
+
+    use super::*;
+
+    const SMITH: ActorId = 1;
+    const PLAYER: ActorId = 2;
+
+    fn actor(persuasion: f32, coins: u32) -> Actor{
+        let mut stats = Stats::new_zero();
+        stats.charisma.persuasion = persuasion;
+        let mut actor = Actor::new(Body::new(vec![], 180, 70), stats);
+        actor.add_gold(coins);
+        actor
+    }
+
+    fn blacksmith() -> (Shop, Actor){
+        let clock = WorldClock::new(0);
+        let mut shop = Shop::for_role(SMITH, Role::Blacksmith, &clock).unwrap();
+        shop.restock_interval = 0;
+        let mut smith = actor(0.0, 0);
+        shop.restock(&mut smith, &clock);
+        (shop, smith)
+    }
+
+    #[test]
+    fn test_buy_and_sell() {
+        let (shop, mut smith) = blacksmith();
+        let mut factions = Factions::new();
+        let mut player = actor(0.0, 100);
+        assert_eq!(shop.buy(&mut smith, &mut player, PLAYER, SWORD_ID, &mut factions), Ok(60));
+        assert_eq!(player.gold(), 40);
+        assert_eq!(shop.buy(&mut smith, &mut player, PLAYER, SWORD_ID, &mut factions), Err(TradeError::NotInStock));
+        assert_eq!(shop.sell(&mut smith, &mut player, PLAYER, SWORD_ID, &mut factions), Ok(20));
+        assert_eq!(player.gold(), 60);
+        assert_eq!(smith.gold(), 140);
+        assert!(factions.reputation(PLAYER, Faction::Merchants) > 0);
+    }
+
+    #[test]
+    fn test_persuasion_and_reputation_lower_prices() {
+        let (shop, _) = blacksmith();
+        let mut factions = Factions::new();
+        let sword = shop.wares[0].clone();
+        let base = shop.buy_price(&sword, &actor(0.0, 0), PLAYER, &factions);
+        let charmer = actor(1.0, 0);
+        assert!(shop.buy_price(&sword, &charmer, PLAYER, &factions) < base);
+        factions.change_reputation(PLAYER, Faction::Merchants, MAX_REPUTATION);
+        assert_eq!(shop.buy_price(&sword, &charmer, PLAYER, &factions), sword.worth());
+        assert!(shop.sell_price(&sword, &charmer, PLAYER, &factions) <= shop.buy_price(&sword, &charmer, PLAYER, &factions));
+
+        factions.change_reputation(PLAYER, Faction::Merchants, -2 * MAX_REPUTATION);
+        let (mut smith, mut player) = (actor(0.0, 0), actor(0.0, 100));
+        assert_eq!(shop.buy(&mut smith, &mut player, PLAYER, SWORD_ID, &mut factions), Err(TradeError::Refused));
+    }
+
+    #[test]
+    fn test_restock() {
+        let clock = WorldClock::new(0);
+        let mut shop = Shop::for_role(SMITH, Role::Shopkeeper, &clock).unwrap();
+        let mut keeper = actor(0.0, 0);
+        assert!(!shop.restock(&mut keeper, &clock));
+        let tomorrow = WorldClock::new(TICKS_PER_DAY);
+        assert!(shop.restock(&mut keeper, &tomorrow));
+        assert_eq!(count_items(&keeper, BREAD_ID), 2);
+        assert_eq!(keeper.gold(), 50);
+
+        keeper.remove_from_inventory(BREAD_ID);
+        assert!(!shop.restock(&mut keeper, &tomorrow));
+        assert!(shop.restock(&mut keeper, &WorldClock::new(2 * TICKS_PER_DAY)));
+        assert_eq!(count_items(&keeper, BREAD_ID), 2);
+        assert_eq!((keeper.inventory().len(), keeper.gold()), (4, 50));
+    }
+
+    #[test]
+    fn test_repair() {
+        let (shop, mut smith) = blacksmith();
+        let mut factions = Factions::new();
+        let mut player = actor(0.0, 100);
+        let mut axe = shop.wares[1].clone();
+        axe.condition = MAX_CONDITION / 5;
+        assert_eq!(labor_worth(&axe), 20);
+        player.add_to_inventory(axe);
+        assert_eq!(shop.repair(&mut smith, &mut player, PLAYER, AXE_ID, &mut factions), Ok(30));
+        assert_eq!(player.inventory().iter().find(|object| object.id == AXE_ID).unwrap().condition, MAX_CONDITION);
+        assert_eq!(shop.repair(&mut smith, &mut player, PLAYER, AXE_ID, &mut factions), Err(TradeError::NothingToRepair));
+        assert_eq!(count_items(&player, AXE_ID), 1);
+
+        let (grocer, mut keeper) = (Shop::for_role(3, Role::Shopkeeper, &WorldClock::new(0)).unwrap(), actor(0.0, 0));
+        assert_eq!(grocer.repair(&mut keeper, &mut player, PLAYER, AXE_ID, &mut factions), Err(TradeError::NotOffered));
+    }
+
+    #[test]
+    fn test_craft() {
+        let (shop, mut smith) = blacksmith();
+        let mut factions = Factions::new();
+        let mut player = actor(0.0, 10);
+        assert_eq!(shop.craft(&mut smith, &mut player, PLAYER, 0, &mut factions), Err(TradeError::MissingMaterials));
+        let material = |id: u32| Object::new(id, String::from("Material"), String::new(), 1, 1);
+        for id in [LOG_ID, STONE_ID, STONE_ID]{
+            player.add_to_inventory(material(id));
+        }
+        assert_eq!(shop.craft(&mut smith, &mut player, PLAYER, 0, &mut factions), Ok(5));
+        assert_eq!(count_items(&player, HAMMER_ID), 1);
+        assert_eq!(count_items(&player, STONE_ID), 0);
+        assert_eq!(player.gold(), 5);
+        assert_eq!(shop.craft(&mut smith, &mut player, PLAYER, 5, &mut factions), Err(TradeError::NotOffered));
+    }
+}
@@ -0,0 +1,129 @@
+use rand::Rng;
+
+use crate::actors::actor::{Actor, ActorId, Control};
+use crate::common::damage::*;
+use crate::world::lighting::visibility;
+use crate::world::weather::Weather;
+
+// chance of hitting a target in plain sight
+const BASE_HIT_CHANCE: f32 = 0.9;
+
+// chance of hitting a target that can't be seen at all, swinging blindly
+const BLIND_HIT_CHANCE: f32 = 0.1;
+
+// damage multiplier of an attack on a target that isn't aware of the attacker
+const SNEAK_ATTACK_MULTIPLIER: f32 = 2.0;
+
+
+#[derive(Debug, PartialEq)]
+pub enum AttackOutcome{
+    Missed,
+    Hit(DamageResult),
+}
+
+
+// chance of the attacker hitting a target standing in the given light
+pub fn hit_chance(attacker: &Actor, light: f32) -> f32{
+    let seen = visibility(&attacker.get_stats().perception, light).clamp(0.0, 1.0);
+    BLIND_HIT_CHANCE + (BASE_HIT_CHANCE - BLIND_HIT_CHANCE) * seen
+}
+
+
+// like hit_chance, but the weather can hide the target and throw projectiles off course
+pub fn ranged_hit_chance(attacker: &Actor, light: f32, weather: Weather) -> f32{
+    let effects = weather.effects();
+    hit_chance(attacker, light * effects.visibility) * effects.ranged_accuracy
+}
+
+
+// an unaware target takes more damage, use DetectionTracker::can_sneak_attack to know if it's the case
+pub fn sneak_attack(damage: Damage) -> Damage{
+    let mut new_damage = damage;
+    new_damage.amount *= SNEAK_ATTACK_MULTIPLIER;
+    new_damage
+}
+
+
+// whether control statuses let the attacker go for this target, a taunted actor only attacks
+// the taunter, a charmed one anybody but the charmer, a terrified, stunned or sleeping one nobody
+pub fn can_attack(attacker: &Actor, target: ActorId) -> bool{
+    match attacker.control(){
+        None => true,
+        Some(Control::Taunted(source)) => target == source,
+        Some(Control::Charmed(source)) => target != source,
+        Some(Control::Terrified(_) | Control::Stunned | Control::Asleep) => false,
+    }
+}
+
+
+// rolls to hit and, on a hit, deals the damage to the target
+pub fn resolve_attack(
+    attacker: &Actor,
+    target: &mut impl Damageable,
+    damage: Damage,
+    light: f32,
+    rng: &mut impl Rng,
+) -> AttackOutcome{
+    if rng.gen::<f32>() >= hit_chance(attacker, light){
+        return AttackOutcome::Missed;
+    }
+    AttackOutcome::Hit(target.damage(damage))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::actors::actor::Body;
+    use crate::common::stats::{PerceptionStat, Stats};
+    use crate::common::status::StatusType;
+
+    use super::*;
+
+    fn actor(perception: PerceptionStat) -> Actor{
+        let mut stats = Stats::new_zero();
+        stats.perception = perception;
+        Actor::new(Body::new(vec![], 180, 70), stats)
+    }
+
+    #[test]
+    fn test_hit_chance_drops_in_the_dark() {
+        let attacker = actor(PerceptionStat::new(1.0, 0.5, 0.5, 0.5, 0.1));
+        assert_eq!(hit_chance(&attacker, 1.0), BASE_HIT_CHANCE);
+        assert!(hit_chance(&attacker, 0.0) < 0.2);
+    }
+
+    #[test]
+    fn test_ranged_hit_chance_in_fog() {
+        let attacker = actor(PerceptionStat::new(1.0, 0.5, 0.5, 0.5, 0.1));
+        assert_eq!(ranged_hit_chance(&attacker, 1.0, Weather::Clear), hit_chance(&attacker, 1.0));
+        assert!(ranged_hit_chance(&attacker, 1.0, Weather::Fog) < hit_chance(&attacker, 1.0));
+    }
+
+    #[test]
+    fn test_resolve_attack() {
+        let attacker = actor(PerceptionStat::new(1.0, 0.5, 0.5, 0.5, 0.0));
+        let mut rng = StdRng::seed_from_u64(0);
+        let damage = Damage{ damage_type: DamageType::Physical(PhysicalDamageType::Slash), amount: 1.0 };
+
+        let mut target = actor(PerceptionStat::new(0.5, 0.5, 0.5, 0.5, 0.5));
+        let hits_in_daylight = (0..100)
+            .filter(|_| resolve_attack(&attacker, &mut target, damage, 1.0, &mut rng) != AttackOutcome::Missed)
+            .count();
+        let hits_in_the_dark = (0..100)
+            .filter(|_| resolve_attack(&attacker, &mut target, damage, 0.0, &mut rng) != AttackOutcome::Missed)
+            .count();
+        assert!(hits_in_daylight > hits_in_the_dark);
+    }
+
+    #[test]
+    fn test_control_restricts_targets() {
+        let mut attacker = actor(PerceptionStat::new(1.0, 0.5, 0.5, 0.5, 0.1));
+        assert!(can_attack(&attacker, 5));
+        attacker.add_status_from(StatusType::Taunt, 3, Some(2));
+        assert!(can_attack(&attacker, 2));
+        assert!(!can_attack(&attacker, 5));
+    }
+}
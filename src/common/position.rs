@@ -1,13 +1,38 @@
 // enums used to identify the position of body parts, used in creating characters and during combat
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum YPosition{
     High,
     Mid,
     Low,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum XPosition{
     Left,
     Mid,
     Right,
-}
\ No newline at end of file
+}
+
+// position of a tile on a local map, (0, 0) is the top left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Coordinates{
+    pub x: u32,
+    pub y: u32,
+}
+
+impl Coordinates{
+    pub fn new(x: u32, y: u32) -> Coordinates{
+        Coordinates{ x, y }
+    }
+
+    // number of steps between two tiles when diagonal moves are allowed
+    pub fn chebyshev_distance(&self, other: Coordinates) -> u32{
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    // number of steps between two tiles when only orthogonal moves are allowed
+    pub fn manhattan_distance(&self, other: Coordinates) -> u32{
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
@@ -233,4 +233,143 @@ impl Stats{
             agility,
         )
     }
-}
\ No newline at end of file
+}
+
+// the stats that are skills between 0 and 1, used to name a single one of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubStat{
+    Vision,
+    Hearing,
+    Smell,
+    Taste,
+    NightVision,
+    Intimidation,
+    Persuasion,
+    Deception,
+    Performance,
+    SensoraryMemory,
+    TextMemory,
+    Learning,
+    Application,
+    Creativity,
+    Climbing,
+    Riding,
+    Swimming,
+    Parkour,
+    Stealth,
+    Acrobatics,
+    Pickpocting,
+}
+
+
+impl SubStat{
+    pub const ALL: [SubStat; 21] = [
+        SubStat::Vision, SubStat::Hearing, SubStat::Smell, SubStat::Taste, SubStat::NightVision,
+        SubStat::Intimidation, SubStat::Persuasion, SubStat::Deception, SubStat::Performance,
+        SubStat::SensoraryMemory, SubStat::TextMemory, SubStat::Learning, SubStat::Application, SubStat::Creativity,
+        SubStat::Climbing, SubStat::Riding, SubStat::Swimming, SubStat::Parkour, SubStat::Stealth, SubStat::Acrobatics, SubStat::Pickpocting,
+    ];
+}
+
+
+impl Stats{
+    pub fn sub_stat(&self, sub_stat: SubStat) -> f32{
+        match sub_stat{
+            SubStat::Vision => self.perception.vision,
+            SubStat::Hearing => self.perception.hearing,
+            SubStat::Smell => self.perception.smell,
+            SubStat::Taste => self.perception.taste,
+            SubStat::NightVision => self.perception.night_vision_modifier,
+            SubStat::Intimidation => self.charisma.intimidation,
+            SubStat::Persuasion => self.charisma.persuasion,
+            SubStat::Deception => self.charisma.deception,
+            SubStat::Performance => self.charisma.performance,
+            SubStat::SensoraryMemory => self.intelligence.sensorary_memory,
+            SubStat::TextMemory => self.intelligence.text_memory,
+            SubStat::Learning => self.intelligence.learning,
+            SubStat::Application => self.intelligence.application,
+            SubStat::Creativity => self.intelligence.creativity,
+            SubStat::Climbing => self.agility.climbing,
+            SubStat::Riding => self.agility.riding,
+            SubStat::Swimming => self.agility.swimming,
+            SubStat::Parkour => self.agility.parkour,
+            SubStat::Stealth => self.agility.stealth,
+            SubStat::Acrobatics => self.agility.acrobatics,
+            SubStat::Pickpocting => self.agility.pickpocting,
+        }
+    }
+
+    pub fn sub_stat_mut(&mut self, sub_stat: SubStat) -> &mut f32{
+        match sub_stat{
+            SubStat::Vision => &mut self.perception.vision,
+            SubStat::Hearing => &mut self.perception.hearing,
+            SubStat::Smell => &mut self.perception.smell,
+            SubStat::Taste => &mut self.perception.taste,
+            SubStat::NightVision => &mut self.perception.night_vision_modifier,
+            SubStat::Intimidation => &mut self.charisma.intimidation,
+            SubStat::Persuasion => &mut self.charisma.persuasion,
+            SubStat::Deception => &mut self.charisma.deception,
+            SubStat::Performance => &mut self.charisma.performance,
+            SubStat::SensoraryMemory => &mut self.intelligence.sensorary_memory,
+            SubStat::TextMemory => &mut self.intelligence.text_memory,
+            SubStat::Learning => &mut self.intelligence.learning,
+            SubStat::Application => &mut self.intelligence.application,
+            SubStat::Creativity => &mut self.intelligence.creativity,
+            SubStat::Climbing => &mut self.agility.climbing,
+            SubStat::Riding => &mut self.agility.riding,
+            SubStat::Swimming => &mut self.agility.swimming,
+            SubStat::Parkour => &mut self.agility.parkour,
+            SubStat::Stealth => &mut self.agility.stealth,
+            SubStat::Acrobatics => &mut self.agility.acrobatics,
+            SubStat::Pickpocting => &mut self.agility.pickpocting,
+        }
+    }
+}
+
+
+// the stats that are whole numbers, levels between 1 and 10, used to name a single one of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Attribute{
+    MaxCarryWeight,
+    BaseMeleeForce,
+    Stamina,
+    MaxSprintSpeed,
+}
+
+
+impl Attribute{
+    pub const ALL: [Attribute; 4] = [Attribute::MaxCarryWeight, Attribute::BaseMeleeForce, Attribute::Stamina, Attribute::MaxSprintSpeed];
+}
+
+
+impl Stats{
+    pub fn attribute(&self, attribute: Attribute) -> u32{
+        match attribute{
+            Attribute::MaxCarryWeight => self.strength.max_carry_weight,
+            Attribute::BaseMeleeForce => self.strength.base_melee_force,
+            Attribute::Stamina => self.endurance.stamina,
+            Attribute::MaxSprintSpeed => self.agility.max_sprint_speed,
+        }
+    }
+
+    pub fn attribute_mut(&mut self, attribute: Attribute) -> &mut u32{
+        match attribute{
+            Attribute::MaxCarryWeight => &mut self.strength.max_carry_weight,
+            Attribute::BaseMeleeForce => &mut self.strength.base_melee_force,
+            Attribute::Stamina => &mut self.endurance.stamina,
+            Attribute::MaxSprintSpeed => &mut self.agility.max_sprint_speed,
+        }
+    }
+
+    // every whole number stat, the attributes and the stamina used
+    pub fn levels_mut(&mut self) -> [&mut u32; 6]{
+        [
+            &mut self.strength.max_carry_weight,
+            &mut self.strength.base_melee_force,
+            &mut self.endurance.stamina,
+            &mut self.endurance.base_stamina_used_for_movement,
+            &mut self.endurance.base_stamina_used_for_fighting,
+            &mut self.agility.max_sprint_speed,
+        ]
+    }
+}
@@ -11,6 +11,7 @@ pub enum StatusType{
     // damage over time
     Burn,
     Freeze,
+    Suffocating,
 
     // good statuses
     Invisible,
@@ -18,7 +19,23 @@ pub enum StatusType{
     Invincible,     // immune to any type of damage or control
     Immortal,       // immune to death, but can take damage or be conrtolled
     UnderwaterBreathing,
+    Airtight,       // breathes without the surrounding air, immune to suffocation
+    HeatResistance,
+    ColdResistance,
 
     // neutral statuses
     Ethereal,   // unable to act, but also unable to be acted upon
-}
\ No newline at end of file
+}
+
+
+impl StatusType{
+    // statuses that take away control of the actor
+    pub fn is_control(&self) -> bool{
+        matches!(self, StatusType::Stun | StatusType::Sleep | StatusType::Taunt | StatusType::Rooted | StatusType::Charm | StatusType::Terror)
+    }
+
+    // statuses that only make sense with an actor causing them
+    pub fn needs_source(&self) -> bool{
+        matches!(self, StatusType::Taunt | StatusType::Charm | StatusType::Terror)
+    }
+}
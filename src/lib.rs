@@ -1,3 +1,5 @@
 pub mod actors;
+pub mod combat;
 pub mod common;
-pub mod objects;
\ No newline at end of file
+pub mod objects;
+pub mod world;
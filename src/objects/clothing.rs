@@ -1,10 +1,10 @@
 use std::collections::HashMap;
 
 use crate::objects::object::Object;
-use crate::common::damage::DamageType;
+use crate::common::damage::{Damage, DamageType, ElementalDamageType};
 
-trait Clothing{
-    fn protect_element(&self);
+pub trait Clothing{
+    fn protect_element(&self, damage: Damage) -> Damage;
 }
 
 trait Armor{
@@ -15,4 +15,52 @@ pub struct ArmorPiece{
     pub object: Object,
     pub armor_low: HashMap<DamageType, u32>,
     pub armor_high: u32,
-}
\ No newline at end of file
+}
+
+pub struct ClothingPiece{
+    pub object: Object,
+    pub elemental_protection: HashMap<ElementalDamageType, f32>,   // fraction of the damage absorbed, between 0 and 1
+}
+
+
+impl ClothingPiece{
+    pub fn new(object: Object, elemental_protection: HashMap<ElementalDamageType, f32>) -> ClothingPiece{
+        ClothingPiece{
+            object,
+            elemental_protection,
+        }
+    }
+
+    pub fn protection(&self, element: ElementalDamageType) -> f32{
+        self.elemental_protection.get(&element).copied().unwrap_or(0.0).clamp(0.0, 1.0)
+    }
+}
+
+
+impl Clothing for ClothingPiece{
+    fn protect_element(&self, damage: Damage) -> Damage{
+        let mut new_damage = damage;
+        if let DamageType::Elemental(element) = damage.damage_type{
+            new_damage.amount *= 1.0 - self.protection(element);
+        }
+        new_damage
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clothing_protect_element() {
+        let object = Object::new(1, String::from("Fur coat"), String::from("A warm fur coat"), 5, 10);
+        let coat = ClothingPiece::new(object, HashMap::from([(ElementalDamageType::Cold, 0.75)]));
+
+        let cold = Damage{ damage_type: DamageType::Elemental(ElementalDamageType::Cold), amount: 20.0 };
+        assert_eq!(coat.protect_element(cold).amount, 5.0);
+
+        let heat = Damage{ damage_type: DamageType::Elemental(ElementalDamageType::Heat), amount: 20.0 };
+        assert_eq!(coat.protect_element(heat).amount, 20.0);
+    }
+}
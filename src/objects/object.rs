@@ -1,3 +1,6 @@
+// condition of an object in perfect shape
+pub const MAX_CONDITION: u32 = 100;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Object{
     pub id: u32,
@@ -5,6 +8,8 @@ pub struct Object{
     pub description: String,
     pub weight: u32,
     pub volume: u32,
+    pub value: u32,         // in gold coins, when in perfect shape
+    pub condition: u32,     // from 0, falling apart, to MAX_CONDITION
 }
 
 impl Object{
@@ -15,8 +20,20 @@ impl Object{
             description: description,
             weight: weight,
             volume: volume,
+            value: 0,
+            condition: MAX_CONDITION,
         }
     }
+
+    pub fn with_value(mut self, value: u32) -> Object{
+        self.value = value;
+        self
+    }
+
+    // value left after wear and tear
+    pub fn worth(&self) -> u32{
+        self.value * self.condition.min(MAX_CONDITION) / MAX_CONDITION
+    }
 }
 
 #[cfg(test)]
@@ -38,5 +55,14 @@ mod tests {
         assert_eq!(test_object.description, description);
         assert_eq!(test_object.weight, weight);
         assert_eq!(test_object.volume, volume);
+        assert_eq!(test_object.condition, MAX_CONDITION);
+    }
+
+    #[test]
+    fn test_object_worth() {
+        let mut sword = Object::new(1, String::from("Sword"), String::from("A sharp blade"), 10, 5).with_value(40);
+        assert_eq!(sword.worth(), 40);
+        sword.condition = MAX_CONDITION / 4;
+        assert_eq!(sword.worth(), 10);
     }
 }
\ No newline at end of file
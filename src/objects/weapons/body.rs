@@ -0,0 +1,55 @@
+use crate::common::damage::*;
+use crate::objects::weapons::melee::MeleeAttack;
+
+
+// claws, fangs, horns and the like, part of a creature's body instead of held
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NaturalWeapon{
+    pub damage_type: DamageType,
+    pub base_attack_damage: f32,
+    pub attack_speed: u32,
+    pub charged_attack_damage_multiplier: f32,     // pouncing, charging, rearing up
+}
+
+
+impl NaturalWeapon{
+    pub fn new(damage_type: DamageType, base_attack_damage: f32, attack_speed: u32, charged_attack_damage_multiplier: f32) -> NaturalWeapon{
+        NaturalWeapon{
+            damage_type,
+            base_attack_damage,
+            attack_speed,
+            charged_attack_damage_multiplier,
+        }
+    }
+}
+
+
+impl MeleeAttack for NaturalWeapon{
+    fn normal_attack(&self) -> Damage{
+        Damage{
+            damage_type: self.damage_type,
+            amount: self.base_attack_damage,
+        }
+    }
+
+    fn charged_attack(&self) -> Damage{
+        Damage{
+            damage_type: self.damage_type,
+            amount: self.base_attack_damage * self.charged_attack_damage_multiplier,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_natural_weapon_attacks() {
+        let claws = NaturalWeapon::new(DamageType::Physical(PhysicalDamageType::Slash), 10.0, 8, 2.0);
+        assert_eq!(claws.normal_attack().amount, 10.0);
+        assert_eq!(claws.charged_attack().amount, 20.0);
+        assert_eq!(claws.charged_attack().damage_type, DamageType::Physical(PhysicalDamageType::Slash));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::objects::object::Object;
 use crate::objects::weapons::common::*;
 use crate::common::damage::*;
+use crate::world::physics::WorldParameters;
 
 
 pub struct Projectile{
@@ -11,13 +12,16 @@ pub struct Projectile{
 
 
 pub trait RangedAttack{
-    fn shoot(&self, projectile: Projectile) -> Damage;
+    // None if the target is further than the weapon can shoot in this world
+    fn shoot(&self, projectile: Projectile, distance: f32, world: &WorldParameters) -> Option<Damage>;
+    fn range(&self, world: &WorldParameters) -> f32;
 }
 
 
 pub struct RangedWeapon{
     pub weapon: Weapon,
     pub base_attack_damage_multiplier: f32,
+    pub base_range: f32,    // range on earth
     pub normal_attack_speed: u32,
     pub melee_attack_damage: u32,
     pub melee_attack_speed: u32,
@@ -25,12 +29,19 @@ pub struct RangedWeapon{
 
 
 impl RangedAttack for RangedWeapon{
-    fn shoot(&self, projectile: Projectile) -> Damage{
+    fn shoot(&self, projectile: Projectile, distance: f32, world: &WorldParameters) -> Option<Damage>{
+        if distance > self.range(world){
+            return None;
+        }
         let damage = Damage{
             damage_type: DamageType::Physical(self.weapon.damage_type),
             amount: projectile.base_damage as f32 * self.base_attack_damage_multiplier,
         };
-        damage
+        Some(damage)
+    }
+
+    fn range(&self, world: &WorldParameters) -> f32{
+        self.base_range * world.projectile_range_modifier()
     }
 }
 
@@ -39,6 +50,7 @@ impl RangedWeapon{
     pub fn new(
         weapon: Weapon, 
         base_attack_damage_multiplier: f32, 
+        base_range: f32,
         normal_attack_speed: u32, 
         melee_attack_damage: u32, 
         melee_attack_speed: u32
@@ -46,6 +58,7 @@ impl RangedWeapon{
         RangedWeapon{
             weapon: weapon,
             base_attack_damage_multiplier: base_attack_damage_multiplier,
+            base_range,
             normal_attack_speed: normal_attack_speed,
             melee_attack_damage: melee_attack_damage,
             melee_attack_speed: melee_attack_speed,
@@ -70,6 +83,7 @@ impl Projectile{
 mod tests{
     use crate::objects::object::Object;
     use crate::objects::weapons::common::*;
+    use crate::world::world_elements::World;
 
     use super::*;
 
@@ -84,6 +98,7 @@ mod tests{
         let ranged_weapon = RangedWeapon::new(
             weapon,
             2.0,
+            30.0,
             10,
             100,
             20,
@@ -92,8 +107,25 @@ mod tests{
             Object::new(1, String::from("Test Ranged Weapon"), String::from("This is a test ranged weapon"), 10, 20),
             50,
         );
-        let result = ranged_weapon.shoot(projectile);
+        let result = ranged_weapon.shoot(projectile, 10.0, &World::Earth.parameters()).unwrap();
         assert_eq!(result.amount, 100.0);
     }
 
+    #[test]
+    fn test_ranged_weapon_range_depends_on_world() {
+        let weapon = Weapon::new(
+            Object::new(1, String::from("Test Ranged Weapon"), String::from("This is a test ranged weapon"), 10, 20),
+            10,
+            0.5,
+            PhysicalDamageType::Pierce,
+        );
+        let ranged_weapon = RangedWeapon::new(weapon, 1.0, 30.0, 10, 100, 20);
+        let projectile = || Projectile::new(
+            Object::new(2, String::from("Arrow"), String::from("A simple arrow"), 1, 1),
+            50,
+        );
+        assert!(ranged_weapon.shoot(projectile(), 50.0, &World::Earth.parameters()).is_none());
+        assert!(ranged_weapon.shoot(projectile(), 50.0, &World::Moon.parameters()).is_some());
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,304 @@
+use crate::common::damage::*;
+use crate::common::position::Coordinates;
+use crate::world::map::{LocalMap, Tile};
+use crate::world::world_elements::{BuildingType, TileType};
+
+// extra cost of walking through a closed door, the time it takes to open it
+const DOOR_MOVEMENT_COST: u32 = 10;
+
+const WALL_SOUND_DAMPING: f32 = 15.0;
+const CLOSED_DOOR_SOUND_DAMPING: f32 = 6.0;
+
+
+// something built on a tile
+#[derive(Debug, Clone, PartialEq)]
+pub enum Structure{
+    Wall,
+    Door(Door),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Door{
+    pub open: bool,
+    pub locked: bool,
+    pub broken: bool,
+    armor_low: u32,     // anything less than this won't do damage
+    armor_high: u32,    // anything more than this will break the door in one blow
+    durability: u32,
+}
+
+// a building placed on a local map
+#[derive(Debug, Clone, PartialEq)]
+pub struct Building{
+    pub building_type: BuildingType,
+    pub origin: Coordinates,    // top left corner
+    pub width: u32,
+    pub height: u32,
+    pub doors: Vec<Coordinates>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildingError{
+    OutOfBounds,
+    TooSmall,
+}
+
+
+impl Structure{
+    // None if it can't be walked through
+    pub fn movement_cost(&self) -> Option<u32>{
+        match self{
+            Structure::Wall => None,
+            Structure::Door(door) if door.locked && !door.broken => None,
+            Structure::Door(door) if door.is_passable() => Some(0),
+            Structure::Door(_) => Some(DOOR_MOVEMENT_COST),
+        }
+    }
+
+    pub fn opacity(&self) -> f32{
+        match self{
+            Structure::Door(door) if door.is_passable() => 0.0,
+            _ => 1.0,
+        }
+    }
+
+    pub fn sound_damping(&self) -> f32{
+        match self{
+            Structure::Wall => WALL_SOUND_DAMPING,
+            Structure::Door(door) if door.is_passable() => 0.0,
+            Structure::Door(_) => CLOSED_DOOR_SOUND_DAMPING,
+        }
+    }
+}
+
+
+impl Door{
+    pub fn new(armor_low: u32, armor_high: u32, durability: u32) -> Door{
+        if armor_low > armor_high{
+            panic!("armor_low must be less than or equal to armor_high");
+        }
+        Door{
+            open: false,
+            locked: false,
+            broken: false,
+            armor_low,
+            armor_high,
+            durability,
+        }
+    }
+
+    // open doors and broken doors can be walked through without stopping
+    pub fn is_passable(&self) -> bool{
+        self.open || self.broken
+    }
+
+    // false if the door is locked
+    pub fn open(&mut self) -> bool{
+        if self.locked && !self.broken{
+            return false;
+        }
+        self.open = true;
+        true
+    }
+
+    // false if the door is broken and can't be closed anymore
+    pub fn close(&mut self) -> bool{
+        if self.broken{
+            return false;
+        }
+        self.open = false;
+        true
+    }
+
+    // only a closed door that isn't broken can be locked
+    pub fn lock(&mut self) -> bool{
+        if self.open || self.broken{
+            return false;
+        }
+        self.locked = true;
+        true
+    }
+
+    pub fn unlock(&mut self){
+        self.locked = false;
+    }
+}
+
+
+impl Damageable for Door{
+    fn damage(&mut self, damage: Damage) -> DamageResult{
+        if self.broken || damage.amount < self.armor_low as f32{
+            return DamageResult::NoDamage;
+        }
+        let amount = damage.amount - self.armor_low as f32;
+        if damage.amount > self.armor_high as f32 || amount >= self.durability as f32{
+            self.durability = 0;
+            self.broken = true;
+            return DamageResult::Destroyed;
+        }
+        self.durability -= amount as u32;
+        DamageResult::Damage(amount)
+    }
+}
+
+
+impl BuildingType{
+    // width and height used when none are given
+    pub fn default_size(&self) -> (u32, u32){
+        match self{
+            BuildingType::House => (6, 5),
+            BuildingType::Tower => (5, 5),
+            BuildingType::Castle => (15, 12),
+            BuildingType::Wall => (10, 1),
+        }
+    }
+
+    pub fn door(&self) -> Door{
+        match self{
+            BuildingType::House => Door::new(5, 60, 40),
+            BuildingType::Tower => Door::new(10, 100, 80),
+            BuildingType::Castle | BuildingType::Wall => Door::new(20, 200, 200),
+        }
+    }
+}
+
+
+// builds on the map, walls around the edges with a floor inside, houses and towers have a door in
+// the middle of the bottom wall, castles also one in the top wall, a wall is a single line with no doors
+pub fn place_building(
+    map: &mut LocalMap,
+    building_type: BuildingType,
+    origin: Coordinates,
+    width: u32,
+    height: u32,
+) -> Result<Building, BuildingError>{
+    if origin.x + width > map.width() || origin.y + height > map.height(){
+        return Err(BuildingError::OutOfBounds);
+    }
+    let hollow = building_type != BuildingType::Wall;
+    if width == 0 || height == 0 || (hollow && (width < 3 || height < 3)){
+        return Err(BuildingError::TooSmall);
+    }
+
+    let mut doors = Vec::new();
+    if hollow{
+        doors.push(Coordinates::new(origin.x + width / 2, origin.y + height - 1));
+        if building_type == BuildingType::Castle{
+            doors.push(Coordinates::new(origin.x + width / 2, origin.y));
+        }
+    }
+
+    for y in origin.y..origin.y + height{
+        for x in origin.x..origin.x + width{
+            let position = Coordinates::new(x, y);
+            let on_edge = !hollow || x == origin.x || y == origin.y || x == origin.x + width - 1 || y == origin.y + height - 1;
+            let mut tile = Tile::new(TileType::Floor);
+            if doors.contains(&position){
+                tile.structure = Some(Structure::Door(building_type.door()));
+            }
+            else if on_edge{
+                tile.structure = Some(Structure::Wall);
+                tile.tile_type = TileType::Stone;
+            }
+            map.set_tile(position, tile);
+        }
+    }
+
+    Ok(Building{
+        building_type,
+        origin,
+        width,
+        height,
+        doors,
+    })
+}
+
+
+impl Building{
+    // the tiles inside the walls
+    pub fn interior(&self) -> Vec<Coordinates>{
+        if self.building_type == BuildingType::Wall{
+            return vec![];
+        }
+        (self.origin.y + 1..self.origin.y + self.height - 1)
+            .flat_map(|y| (self.origin.x + 1..self.origin.x + self.width - 1).map(move |x| Coordinates::new(x, y)))
+            .collect()
+    }
+
+    pub fn contains(&self, position: Coordinates) -> bool{
+        (self.origin.x..self.origin.x + self.width).contains(&position.x)
+            && (self.origin.y..self.origin.y + self.height).contains(&position.y)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::world::fov::has_line_of_sight;
+    use crate::world::pathfinding::{find_path, Movement};
+    use crate::world::sound::{Sound, SoundField};
+
+    use super::*;
+
+    fn house() -> (LocalMap, Building){
+        let mut map = LocalMap::new(20, 20, TileType::Grass);
+        let building = place_building(&mut map, BuildingType::House, Coordinates::new(5, 5), 6, 5).unwrap();
+        (map, building)
+    }
+
+    #[test]
+    fn test_place_building() {
+        let (map, building) = house();
+        assert_eq!(building.doors, vec![Coordinates::new(8, 9)]);
+        assert_eq!(building.interior().len(), 4 * 3);
+        assert_eq!(map.get_tile(Coordinates::new(6, 6)).unwrap().tile_type, TileType::Floor);
+        assert_eq!(map.get_tile(Coordinates::new(5, 5)).unwrap().structure, Some(Structure::Wall));
+        assert_eq!(place_building(&mut LocalMap::new(5, 5, TileType::Grass), BuildingType::House, Coordinates::new(2, 2), 6, 5), Err(BuildingError::OutOfBounds));
+    }
+
+    #[test]
+    fn test_walls_block_movement_sight_and_sound() {
+        let (mut map, _) = house();
+        let (inside, outside) = (Coordinates::new(7, 7), Coordinates::new(7, 2));
+        assert!(!has_line_of_sight(&map, inside, outside));
+
+        let muffled = SoundField::new(&map, Sound::shout(inside, 1)).loudness_at(Coordinates::new(7, 4));
+        map.set_tile(Coordinates::new(7, 5), Tile::new(TileType::Floor));
+        let open = SoundField::new(&map, Sound::shout(inside, 1)).loudness_at(Coordinates::new(7, 4));
+        assert!(muffled < open);
+
+        map.door_mut(Coordinates::new(8, 9)).unwrap().lock();
+        map.set_tile(Coordinates::new(7, 5), Tile{ structure: Some(Structure::Wall), ..Tile::new(TileType::Stone) });
+        assert!(find_path(&map, outside, inside, Movement::Diagonal).is_none());
+    }
+
+    #[test]
+    fn test_doors() {
+        let (mut map, building) = house();
+        let (door, inside, outside) = (building.doors[0], Coordinates::new(8, 8), Coordinates::new(8, 12));
+        assert!(find_path(&map, outside, inside, Movement::Orthogonal).is_some());
+        assert!(!has_line_of_sight(&map, outside, inside));
+
+        assert!(map.door_mut(door).unwrap().open());
+        assert!(has_line_of_sight(&map, outside, inside));
+        assert!(map.door_mut(door).unwrap().close());
+        assert!(map.door_mut(door).unwrap().lock());
+        assert!(!map.door_mut(door).unwrap().open());
+        assert!(find_path(&map, outside, inside, Movement::Orthogonal).is_none());
+    }
+
+    #[test]
+    fn test_break_door() {
+        let (mut map, building) = house();
+        let door_position = building.doors[0];
+        let door = map.door_mut(door_position).unwrap();
+        door.lock();
+        let kick = Damage{ damage_type: DamageType::Physical(PhysicalDamageType::Impact), amount: 3.0 };
+        assert_eq!(door.damage(kick), DamageResult::NoDamage);
+        let axe = Damage{ damage_type: DamageType::Physical(PhysicalDamageType::Slash), amount: 30.0 };
+        assert_eq!(door.damage(axe), DamageResult::Damage(25.0));
+        assert_eq!(door.damage(axe), DamageResult::Destroyed);
+        assert!(door.is_passable());
+        assert!(find_path(&map, Coordinates::new(8, 12), Coordinates::new(8, 8), Movement::Orthogonal).is_some());
+    }
+}
@@ -0,0 +1,168 @@
+use rand::Rng;
+
+use crate::actors::actor::Actor;
+use crate::common::damage::*;
+use crate::common::status::StatusType;
+use crate::world::physics::is_protected;
+use crate::world::world_elements::{Biome, TileType};
+
+// how long a status inflicted by the environment lasts after leaving it
+const HAZARD_STATUS_DURATION: u32 = 3;
+
+// chance of slipping on ice for someone with no acrobatics at all
+const BASE_SLIP_CHANCE: f64 = 0.3;
+
+
+// something in the surroundings that hurts or hinders whoever stands in it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hazard{
+    Heat(f32),      // heat damage per tick
+    Cold(f32),      // cold damage per tick
+    Slippery,       // might fall and be stunned
+    DeepWater,      // drowns without a way to breathe underwater
+}
+
+
+impl Biome{
+    pub fn hazards(&self) -> Vec<Hazard>{
+        match self{
+            Biome::LavaOcean => vec![Hazard::Heat(20.0)],
+            Biome::HotDesert => vec![Hazard::Heat(1.0)],
+            Biome::Glacier => vec![Hazard::Cold(3.0), Hazard::Slippery],
+            Biome::Tundra | Biome::ColdDesert => vec![Hazard::Cold(1.0)],
+            Biome::Ocean => vec![Hazard::DeepWater],
+            _ => vec![],
+        }
+    }
+}
+
+
+impl TileType{
+    pub fn hazards(&self) -> Vec<Hazard>{
+        match self{
+            TileType::Ice => vec![Hazard::Slippery],
+            _ => vec![],
+        }
+    }
+}
+
+
+// every hazard of a tile in a biome, slipping is only checked once even if both are slippery
+pub fn hazards_at(biome: Biome, tile_type: TileType) -> Vec<Hazard>{
+    let mut hazards = biome.hazards();
+    for hazard in tile_type.hazards(){
+        if !hazards.contains(&hazard){
+            hazards.push(hazard);
+        }
+    }
+    hazards
+}
+
+
+// applies one tick of the hazards of the place the actor is standing in
+pub fn apply_hazards(actor: &mut Actor, biome: Biome, tile_type: TileType, rng: &mut impl Rng) -> Vec<DamageResult>{
+    let mut results = Vec::new();
+    for hazard in hazards_at(biome, tile_type){
+        match hazard{
+            Hazard::Heat(amount) => {
+                results.push(elemental_hazard(actor, ElementalDamageType::Heat, amount, StatusType::Burn));
+            }
+            Hazard::Cold(amount) => {
+                results.push(elemental_hazard(actor, ElementalDamageType::Cold, amount, StatusType::Freeze));
+            }
+            Hazard::Slippery => {
+                let slip_chance = BASE_SLIP_CHANCE * (1.0 - actor.get_stats().agility.acrobatics.clamp(0.0, 1.0) as f64);
+                if !actor.has_status(StatusType::Unstoppable) && rng.gen_bool(slip_chance){
+                    actor.add_status(StatusType::Stun, 1);
+                }
+            }
+            Hazard::DeepWater => {
+                if !actor.has_status(StatusType::UnderwaterBreathing) && !is_protected(actor, StatusType::Suffocating){
+                    actor.add_status(StatusType::Suffocating, 1);
+                }
+            }
+        }
+    }
+    results
+}
+
+
+// damages the actor and inflicts the status if any damage goes through the clothing
+fn elemental_hazard(actor: &mut Actor, element: ElementalDamageType, amount: f32, status: StatusType) -> DamageResult{
+    if is_protected(actor, status){
+        return DamageResult::NoDamage;
+    }
+    let result = actor.damage(Damage{ damage_type: DamageType::Elemental(element), amount });
+    if matches!(result, DamageResult::Damage(amount) if amount > 0.0){
+        actor.add_status(status, HAZARD_STATUS_DURATION);
+    }
+    result
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::actors::actor::{Body, MAX_HEALTH};
+    use crate::common::stats::Stats;
+    use crate::objects::clothing::ClothingPiece;
+    use crate::objects::object::Object;
+
+    use super::*;
+
+    fn actor() -> Actor{
+        Actor::new(Body::new(vec![], 180, 70), Stats::new_zero())
+    }
+
+    #[test]
+    fn test_lava_burns() {
+        let mut actor = actor();
+        apply_hazards(&mut actor, Biome::LavaOcean, TileType::Stone, &mut StdRng::seed_from_u64(0));
+        assert_eq!(actor.health(), MAX_HEALTH - 20.0);
+        assert!(actor.has_status(StatusType::Burn));
+    }
+
+    #[test]
+    fn test_clothing_mitigates_cold() {
+        let mut actor = actor();
+        let coat = Object::new(1, String::from("Fur coat"), String::from("A warm fur coat"), 5, 10);
+        actor.wear(ClothingPiece::new(coat, HashMap::from([(ElementalDamageType::Cold, 1.0)])));
+        apply_hazards(&mut actor, Biome::Glacier, TileType::Snow, &mut StdRng::seed_from_u64(0));
+        assert_eq!(actor.health(), MAX_HEALTH);
+        assert!(!actor.has_status(StatusType::Freeze));
+    }
+
+    #[test]
+    fn test_drowning() {
+        let mut actor = actor();
+        apply_hazards(&mut actor, Biome::Ocean, TileType::Sand, &mut StdRng::seed_from_u64(0));
+        assert!(actor.has_status(StatusType::Suffocating));
+
+        let mut diver = self::actor();
+        diver.add_status(StatusType::UnderwaterBreathing, 10);
+        apply_hazards(&mut diver, Biome::Ocean, TileType::Sand, &mut StdRng::seed_from_u64(0));
+        assert!(!diver.has_status(StatusType::Suffocating));
+    }
+
+    #[test]
+    fn test_slipping_on_ice() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut clumsy = actor();
+        for _ in 0..100{
+            apply_hazards(&mut clumsy, Biome::Plains, TileType::Ice, &mut rng);
+        }
+        assert!(clumsy.has_status(StatusType::Stun));
+
+        let mut stats = Stats::new_zero();
+        stats.agility.acrobatics = 1.0;
+        let mut acrobat = Actor::new(Body::new(vec![], 180, 70), stats);
+        for _ in 0..100{
+            apply_hazards(&mut acrobat, Biome::Plains, TileType::Ice, &mut rng);
+        }
+        assert!(!acrobat.has_status(StatusType::Stun));
+    }
+}
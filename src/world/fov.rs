@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use crate::actors::actor::ActorId;
+use crate::common::position::Coordinates;
+use crate::world::map::LocalMap;
+
+// below this much clarity a tile is considered out of sight
+const MIN_CLARITY: f32 = 0.1;
+
+// multipliers that map the first octant onto each of the eight octants
+const OCTANTS: [[i64; 4]; 8] = [
+    [1, 0, 0, 1],
+    [0, 1, 1, 0],
+    [0, -1, 1, 0],
+    [-1, 0, 0, 1],
+    [-1, 0, 0, -1],
+    [0, -1, -1, 0],
+    [0, 1, -1, 0],
+    [1, 0, 0, -1],
+];
+
+
+// tiles seen from an origin, with how clearly each of them is seen between 0 and 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldOfView{
+    pub origin: Coordinates,
+    pub radius: u32,
+    clarity: HashMap<Coordinates, f32>,
+}
+
+// field of view of every actor, recomputed only when the actor moves or the map changes
+pub struct FovCache{
+    entries: HashMap<ActorId, (u64, FieldOfView)>,
+}
+
+
+impl FieldOfView{
+    // recursive shadowcasting finds the tiles not hidden by anything fully opaque,
+    // then partially opaque tiles on the way dim the ones behind them
+    pub fn new(map: &LocalMap, origin: Coordinates, radius: u32) -> FieldOfView{
+        let mut clarity = HashMap::new();
+        if map.contains(origin){
+            clarity.insert(origin, 1.0);
+            let mut visible = Vec::new();
+            for octant in OCTANTS{
+                cast_light(map, origin, radius as i64, 1, 1.0, 0.0, octant, &mut visible);
+            }
+            for position in visible{
+                let line_clarity = line_of_sight(map, origin, position);
+                if line_clarity >= MIN_CLARITY{
+                    clarity.insert(position, line_clarity);
+                }
+            }
+        }
+        FieldOfView{
+            origin,
+            radius,
+            clarity,
+        }
+    }
+
+    pub fn is_visible(&self, position: Coordinates) -> bool{
+        self.clarity.contains_key(&position)
+    }
+
+    // 0 if the tile can't be seen
+    pub fn clarity(&self, position: Coordinates) -> f32{
+        self.clarity.get(&position).copied().unwrap_or(0.0)
+    }
+
+    pub fn visible_tiles(&self) -> impl Iterator<Item = &Coordinates>{
+        self.clarity.keys()
+    }
+}
+
+
+#[allow(clippy::too_many_arguments)]
+fn cast_light(
+    map: &LocalMap,
+    origin: Coordinates,
+    radius: i64,
+    row: i64,
+    mut start_slope: f32,
+    end_slope: f32,
+    [xx, xy, yx, yy]: [i64; 4],
+    visible: &mut Vec<Coordinates>,
+){
+    if start_slope < end_slope{
+        return;
+    }
+    let mut next_start_slope = start_slope;
+    for distance in row..=radius{
+        let mut blocked = false;
+        let dy = -distance;
+        for dx in -distance..=0{
+            let left_slope = (dx as f32 - 0.5) / (dy as f32 + 0.5);
+            let right_slope = (dx as f32 + 0.5) / (dy as f32 - 0.5);
+            if start_slope < right_slope{
+                continue;
+            }
+            if end_slope > left_slope{
+                break;
+            }
+
+            let x = origin.x as i64 + dx * xx + dy * xy;
+            let y = origin.y as i64 + dx * yx + dy * yy;
+            let position = (x >= 0 && y >= 0).then(|| Coordinates::new(x as u32, y as u32)).filter(|position| map.contains(*position));
+            if let Some(position) = position{
+                if dx * dx + dy * dy <= radius * radius{
+                    visible.push(position);
+                }
+            }
+
+            let opaque = position.is_none_or(|position| map.opacity(position) >= 1.0);
+            if blocked{
+                if opaque{
+                    next_start_slope = right_slope;
+                }
+                else{
+                    blocked = false;
+                    start_slope = next_start_slope;
+                }
+            }
+            else if opaque && distance < radius{
+                blocked = true;
+                cast_light(map, origin, radius, distance + 1, start_slope, left_slope, [xx, xy, yx, yy], visible);
+                next_start_slope = right_slope;
+            }
+        }
+        if blocked{
+            break;
+        }
+    }
+}
+
+
+// how clearly `to` can be seen from `from`, 1 if nothing is in the way and 0 if it is hidden,
+// only the tiles in between count, not the ones at the ends
+pub fn line_of_sight(map: &LocalMap, from: Coordinates, to: Coordinates) -> f32{
+    let line = bresenham_line(from, to);
+    if line.len() <= 2{
+        return 1.0;
+    }
+    line[1..line.len() - 1]
+        .iter()
+        .map(|position| 1.0 - map.opacity(*position))
+        .product()
+}
+
+
+pub fn has_line_of_sight(map: &LocalMap, from: Coordinates, to: Coordinates) -> bool{
+    line_of_sight(map, from, to) >= MIN_CLARITY
+}
+
+
+fn bresenham_line(from: Coordinates, to: Coordinates) -> Vec<Coordinates>{
+    let (mut x, mut y) = (from.x as i64, from.y as i64);
+    let (x1, y1) = (to.x as i64, to.y as i64);
+    let dx = (x1 - x).abs();
+    let dy = -(y1 - y).abs();
+    let step_x = if x < x1 { 1 } else { -1 };
+    let step_y = if y < y1 { 1 } else { -1 };
+    let mut error = dx + dy;
+    let mut line = Vec::new();
+    loop{
+        line.push(Coordinates::new(x as u32, y as u32));
+        if x == x1 && y == y1{
+            return line;
+        }
+        let doubled_error = 2 * error;
+        if doubled_error >= dy{
+            error += dy;
+            x += step_x;
+        }
+        if doubled_error <= dx{
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+
+impl FovCache{
+    pub fn new() -> FovCache{
+        FovCache{
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn field_of_view(&mut self, map: &LocalMap, actor: ActorId, position: Coordinates, radius: u32) -> &FieldOfView{
+        let stale = self.entries.get(&actor).is_none_or(|(revision, field_of_view)| {
+            *revision != map.revision() || field_of_view.origin != position || field_of_view.radius != radius
+        });
+        if stale{
+            self.entries.insert(actor, (map.revision(), FieldOfView::new(map, position, radius)));
+        }
+        &self.entries[&actor].1
+    }
+
+    // to be called when the actor moves
+    pub fn invalidate(&mut self, actor: ActorId){
+        self.entries.remove(&actor);
+    }
+}
+
+
+impl Default for FovCache{
+    fn default() -> FovCache{
+        FovCache::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::object::Object;
+    use crate::world::world_elements::{TileType, WorldObject, WorldObjectType};
+
+    use super::*;
+
+    fn world_object(object_type: WorldObjectType) -> WorldObject{
+        WorldObject::new(Object::new(1, String::from("Obstacle"), String::from("Something in the way"), 100, 100), object_type)
+    }
+
+    #[test]
+    fn test_open_field() {
+        let map = LocalMap::new(11, 11, TileType::Grass);
+        let field_of_view = FieldOfView::new(&map, Coordinates::new(5, 5), 3);
+        assert!(field_of_view.is_visible(Coordinates::new(5, 2)));
+        assert!(field_of_view.is_visible(Coordinates::new(7, 7)));
+        assert!(!field_of_view.is_visible(Coordinates::new(5, 1)));
+        assert_eq!(field_of_view.clarity(Coordinates::new(6, 6)), 1.0);
+    }
+
+    #[test]
+    fn test_rock_hides_what_is_behind() {
+        let mut map = LocalMap::new(11, 11, TileType::Grass);
+        map.add_object(Coordinates::new(5, 4), world_object(WorldObjectType::Rock));
+        let field_of_view = FieldOfView::new(&map, Coordinates::new(5, 5), 5);
+        assert!(field_of_view.is_visible(Coordinates::new(5, 4)));
+        assert!(!field_of_view.is_visible(Coordinates::new(5, 2)));
+        assert!(field_of_view.is_visible(Coordinates::new(8, 2)));
+        assert!(!has_line_of_sight(&map, Coordinates::new(5, 5), Coordinates::new(5, 0)));
+    }
+
+    #[test]
+    fn test_trees_partially_block_sight() {
+        let mut map = LocalMap::new(11, 11, TileType::Grass);
+        map.add_object(Coordinates::new(5, 4), world_object(WorldObjectType::Tree));
+        let field_of_view = FieldOfView::new(&map, Coordinates::new(5, 5), 5);
+        assert_eq!(field_of_view.clarity(Coordinates::new(5, 2)), 0.5);
+        assert_eq!(line_of_sight(&map, Coordinates::new(5, 5), Coordinates::new(5, 2)), 0.5);
+        map.add_object(Coordinates::new(5, 3), world_object(WorldObjectType::Tree));
+        map.add_object(Coordinates::new(5, 2), world_object(WorldObjectType::Tree));
+        map.add_object(Coordinates::new(5, 1), world_object(WorldObjectType::Tree));
+        assert!(!has_line_of_sight(&map, Coordinates::new(5, 5), Coordinates::new(5, 0)));
+    }
+
+    #[test]
+    fn test_fov_cache() {
+        let mut map = LocalMap::new(11, 11, TileType::Grass);
+        let mut cache = FovCache::new();
+        assert!(cache.field_of_view(&map, 1, Coordinates::new(5, 5), 5).is_visible(Coordinates::new(5, 1)));
+
+        map.add_object(Coordinates::new(5, 4), world_object(WorldObjectType::Rock));
+        assert!(!cache.field_of_view(&map, 1, Coordinates::new(5, 5), 5).is_visible(Coordinates::new(5, 1)));
+
+        let moved = cache.field_of_view(&map, 1, Coordinates::new(6, 5), 5);
+        assert_eq!(moved.origin, Coordinates::new(6, 5));
+        assert!(moved.is_visible(Coordinates::new(6, 1)));
+    }
+}
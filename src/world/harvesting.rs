@@ -0,0 +1,192 @@
+use crate::actors::actor::Actor;
+use crate::common::damage::*;
+use crate::common::position::Coordinates;
+use crate::objects::object::Object;
+use crate::world::map::LocalMap;
+use crate::world::world_elements::{WorldObject, WorldObjectType};
+
+// ids of the resources obtained from the world
+pub const LOG_ID: u32 = 1001;
+pub const STONE_ID: u32 = 1002;
+pub const BERRIES_ID: u32 = 1003;
+
+const LOGS_PER_TREE: u32 = 3;
+const STONES_PER_ROCK: u32 = 2;
+
+
+#[derive(Debug, PartialEq)]
+pub enum HarvestResult{
+    NothingThere,
+    Damaged(DamageResult),      // hit, but still standing
+    Harvested(Vec<Object>),     // destroyed or gathered, these are the resources obtained
+    Depleted,                   // nothing left to gather
+}
+
+
+impl WorldObjectType{
+    // how well a type of damage works on this object, an axe is good for trees and a hammer for rocks
+    pub fn damage_effectiveness(&self, damage_type: DamageType) -> f32{
+        match (self, damage_type){
+            (WorldObjectType::Tree, DamageType::Physical(PhysicalDamageType::Slash)) => 1.0,
+            (WorldObjectType::Tree, DamageType::Physical(PhysicalDamageType::Impact)) => 0.3,
+            (WorldObjectType::Tree, DamageType::Elemental(ElementalDamageType::Heat)) => 1.5,
+            (WorldObjectType::Rock, DamageType::Physical(PhysicalDamageType::Impact)) => 1.0,
+            (WorldObjectType::Rock, DamageType::Physical(PhysicalDamageType::Pierce)) => 0.6,
+            (WorldObjectType::Rock, _) => 0.0,
+            (WorldObjectType::Bush | WorldObjectType::Flower, _) => 1.0,
+            (_, DamageType::Poison(_)) => 0.0,
+            _ => 0.2,
+        }
+    }
+
+    // resources obtained when the object is destroyed
+    pub fn drops(&self) -> Vec<Object>{
+        match self{
+            WorldObjectType::Tree => (0..LOGS_PER_TREE).map(|_| log()).collect(),
+            WorldObjectType::Rock => (0..STONES_PER_ROCK).map(|_| stone()).collect(),
+            WorldObjectType::Bush | WorldObjectType::Flower => vec![],
+        }
+    }
+}
+
+
+impl Damageable for WorldObject{
+    fn damage(&mut self, damage: Damage) -> DamageResult{
+        let amount = (damage.amount * self.object_type.damage_effectiveness(damage.damage_type)) as u32;
+        if amount == 0{
+            DamageResult::NoDamage
+        }
+        else if amount >= self.durability{
+            self.durability = 0;
+            DamageResult::Destroyed
+        }
+        else{
+            self.durability -= amount;
+            DamageResult::Damage(amount as f32)
+        }
+    }
+}
+
+
+// hits an object on the tile, chopping trees and mining rocks, what's destroyed is removed from the map
+pub fn strike(map: &mut LocalMap, position: Coordinates, object_type: WorldObjectType, damage: Damage) -> HarvestResult{
+    let Some(world_object) = map.world_object_mut(position, object_type) else{
+        return HarvestResult::NothingThere;
+    };
+    match world_object.damage(damage){
+        DamageResult::Destroyed | DamageResult::Killed => {
+            map.remove_object(position, object_type);
+            HarvestResult::Harvested(object_type.drops())
+        }
+        result => HarvestResult::Damaged(result),
+    }
+}
+
+
+// picks berries from bushes and flowers from the ground, a picked flower is removed from the map
+pub fn gather(map: &mut LocalMap, position: Coordinates, object_type: WorldObjectType) -> HarvestResult{
+    let Some(world_object) = map.world_object_mut(position, object_type) else{
+        return HarvestResult::NothingThere;
+    };
+    if world_object.yields == 0{
+        return HarvestResult::Depleted;
+    }
+    world_object.yields -= 1;
+    match object_type{
+        WorldObjectType::Flower => {
+            let flower = map.remove_object(position, object_type).map(|flower| flower.object);
+            HarvestResult::Harvested(flower.into_iter().collect())
+        }
+        WorldObjectType::Bush => HarvestResult::Harvested(vec![berries()]),
+        WorldObjectType::Tree | WorldObjectType::Rock => HarvestResult::Depleted,
+    }
+}
+
+
+// puts whatever was harvested in the actor's inventory, returns true if anything was harvested
+pub fn collect(actor: &mut Actor, result: HarvestResult) -> bool{
+    match result{
+        HarvestResult::Harvested(objects) => {
+            let harvested = !objects.is_empty();
+            for object in objects{
+                actor.add_to_inventory(object);
+            }
+            harvested
+        }
+        _ => false,
+    }
+}
+
+
+fn log() -> Object{
+    Object::new(LOG_ID, String::from("Log"), String::from("A piece of a chopped tree"), 20, 30).with_value(2)
+}
+
+fn stone() -> Object{
+    Object::new(STONE_ID, String::from("Stone"), String::from("A chunk of a mined rock"), 10, 5).with_value(1)
+}
+
+fn berries() -> Object{
+    Object::new(BERRIES_ID, String::from("Berries"), String::from("A handful of berries, good for cooking and brewing"), 1, 1).with_value(1)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::actor::Body;
+    use crate::common::stats::Stats;
+    use crate::world::world_elements::TileType;
+
+    use super::*;
+
+    fn map_with(object_type: WorldObjectType) -> LocalMap{
+        let mut map = LocalMap::new(3, 3, TileType::Grass);
+        let object = Object::new(1, format!("{:?}", object_type), String::from("Part of the landscape"), 100, 100);
+        map.add_object(Coordinates::new(1, 1), WorldObject::new(object, object_type));
+        map
+    }
+
+    fn hit(damage_type: PhysicalDamageType, amount: f32) -> Damage{
+        Damage{ damage_type: DamageType::Physical(damage_type), amount }
+    }
+
+    #[test]
+    fn test_chop_tree() {
+        let mut map = map_with(WorldObjectType::Tree);
+        let position = Coordinates::new(1, 1);
+        assert_eq!(strike(&mut map, position, WorldObjectType::Tree, hit(PhysicalDamageType::Slash, 60.0)), HarvestResult::Damaged(DamageResult::Damage(60.0)));
+        let result = strike(&mut map, position, WorldObjectType::Tree, hit(PhysicalDamageType::Slash, 60.0));
+
+        let mut actor = Actor::new(Body::new(vec![], 180, 70), Stats::new_zero());
+        assert!(collect(&mut actor, result));
+        assert_eq!(actor.inventory().len(), LOGS_PER_TREE as usize);
+        assert_eq!(map.movement_cost(position), Some(10));
+    }
+
+    #[test]
+    fn test_right_tool_for_the_rock() {
+        let mut map = map_with(WorldObjectType::Rock);
+        let position = Coordinates::new(1, 1);
+        assert_eq!(strike(&mut map, position, WorldObjectType::Rock, hit(PhysicalDamageType::Slash, 500.0)), HarvestResult::Damaged(DamageResult::NoDamage));
+        assert!(matches!(strike(&mut map, position, WorldObjectType::Rock, hit(PhysicalDamageType::Impact, 500.0)), HarvestResult::Harvested(_)));
+        assert_eq!(strike(&mut map, position, WorldObjectType::Rock, hit(PhysicalDamageType::Impact, 500.0)), HarvestResult::NothingThere);
+    }
+
+    #[test]
+    fn test_gather_berries_until_depleted() {
+        let mut map = map_with(WorldObjectType::Bush);
+        let position = Coordinates::new(1, 1);
+        for _ in 0..WorldObjectType::Bush.yields(){
+            assert_eq!(gather(&mut map, position, WorldObjectType::Bush), HarvestResult::Harvested(vec![berries()]));
+        }
+        assert_eq!(gather(&mut map, position, WorldObjectType::Bush), HarvestResult::Depleted);
+    }
+
+    #[test]
+    fn test_pick_flower() {
+        let mut map = map_with(WorldObjectType::Flower);
+        let position = Coordinates::new(1, 1);
+        assert!(matches!(gather(&mut map, position, WorldObjectType::Flower), HarvestResult::Harvested(flowers) if flowers.len() == 1));
+        assert!(map.get_tile(position).unwrap().objects.is_empty());
+    }
+}
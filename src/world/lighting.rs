@@ -0,0 +1,99 @@
+use crate::common::position::Coordinates;
+use crate::common::stats::PerceptionStat;
+use crate::world::map::LocalMap;
+use crate::world::time::WorldClock;
+use crate::world::world_elements::TileType;
+
+// fraction of the sunlight that gets inside buildings through doors and windows
+const INDOOR_SUNLIGHT: f32 = 0.3;
+
+
+// torches, fireplaces, lamps... anything that lights up the tiles around it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightSource{
+    pub position: Coordinates,
+    pub radius: u32,
+    pub intensity: f32,     // light on the tile of the source, fades linearly to 0 at the radius
+}
+
+
+impl LightSource{
+    pub fn new(position: Coordinates, radius: u32, intensity: f32) -> LightSource{
+        LightSource{
+            position,
+            radius,
+            intensity,
+        }
+    }
+
+    pub fn torch(position: Coordinates) -> LightSource{
+        LightSource::new(position, 5, 0.8)
+    }
+
+    pub fn light_at(&self, position: Coordinates) -> f32{
+        let distance = self.position.chebyshev_distance(position);
+        if distance >= self.radius{
+            return 0.0;
+        }
+        self.intensity * (1.0 - distance as f32 / self.radius as f32)
+    }
+}
+
+
+// light on a tile between 0 (pitch black) and 1 (full daylight)
+pub fn light_level(map: &LocalMap, clock: &WorldClock, position: Coordinates) -> f32{
+    let indoors = map.get_tile(position).is_some_and(|tile| tile.tile_type == TileType::Floor);
+    let sunlight = if indoors { clock.sunlight() * INDOOR_SUNLIGHT } else { clock.sunlight() };
+    let artificial = map.lights().iter().map(|light| light.light_at(position)).fold(0.0, f32::max);
+    sunlight.max(artificial).min(1.0)
+}
+
+
+// how well someone sees with the given light, in full light it's just the vision, in the dark it's
+// the vision multiplied by the night vision modifier, in between it's a mix of the two
+pub fn visibility(perception: &PerceptionStat, light: f32) -> f32{
+    let light = light.clamp(0.0, 1.0);
+    perception.vision * (light + (1.0 - light) * perception.night_vision_modifier)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::world::map::Tile;
+
+    use super::*;
+
+    #[test]
+    fn test_light_level_day_and_night() {
+        let map = LocalMap::new(10, 10, TileType::Grass);
+        let position = Coordinates::new(5, 5);
+        assert_eq!(light_level(&map, &WorldClock::at(0, 12, 0), position), 1.0);
+        assert!(light_level(&map, &WorldClock::at(0, 0, 0), position) < 0.1);
+    }
+
+    #[test]
+    fn test_torch_lights_the_night() {
+        let mut map = LocalMap::new(10, 10, TileType::Grass);
+        map.add_light(LightSource::torch(Coordinates::new(5, 5)));
+        let midnight = WorldClock::at(0, 0, 0);
+        assert_eq!(light_level(&map, &midnight, Coordinates::new(5, 5)), 0.8);
+        assert!(light_level(&map, &midnight, Coordinates::new(6, 5)) > light_level(&map, &midnight, Coordinates::new(8, 5)));
+        assert!(light_level(&map, &midnight, Coordinates::new(0, 0)) < 0.1);
+    }
+
+    #[test]
+    fn test_indoors_is_darker() {
+        let mut map = LocalMap::new(10, 10, TileType::Grass);
+        map.set_tile(Coordinates::new(1, 1), Tile::new(TileType::Floor));
+        let noon = WorldClock::at(0, 12, 0);
+        assert!(light_level(&map, &noon, Coordinates::new(1, 1)) < light_level(&map, &noon, Coordinates::new(2, 2)));
+    }
+
+    #[test]
+    fn test_visibility() {
+        let owl = PerceptionStat::new(1.0, 0.5, 0.5, 0.5, 0.8);
+        let human = PerceptionStat::new(1.0, 0.5, 0.5, 0.5, 0.1);
+        assert_eq!(visibility(&owl, 1.0), visibility(&human, 1.0));
+        assert!(visibility(&owl, 0.0) > visibility(&human, 0.0));
+    }
+}
@@ -1,37 +1,294 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::common::position::Coordinates;
+use crate::world::buildings::{Door, Structure};
+use crate::world::lighting::LightSource;
+use crate::world::time::WorldClock;
+use crate::world::weather::{weather_at, wind_at, Weather, Wind};
 use crate::world::world_elements::*;
 
-struct LocalMap{
+// ids handed out to local maps, so anything derived from a map can tell it apart from others
+static NEXT_MAP_ID: AtomicU64 = AtomicU64::new(0);
+
+pub struct LocalMap{
     tiles: Vec<Vec<Tile>>,
     width: u32,
     height: u32,
+    id: u64,
+    revision: u64,  // bumped every time a tile changes, used to invalidate anything derived from the tiles
+    lights: Vec<LightSource>,
 }
 
-struct Location{
-    name: String,
-    description: String,
-    x: u32,
-    y: u32,
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Location{
+    pub name: String,
+    pub description: String,
+    pub x: u32,
+    pub y: u32,
 }
 
-struct Tile{
-    tyle_type: TileType,
-    objects: Set<WorldObject>,
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tile{
+    pub tile_type: TileType,
+    pub objects: Vec<WorldObject>,
+    pub structure: Option<Structure>,
 }
 
-struct Map{
+pub struct Map{
     biomes: Vec<Vec<Biome>>,
     locations: HashMap<Location, LocalMap>,
     width: u32,
     height: u32,
+    seed: u64,      // drives everything random about the map, like the weather
 }
 
 impl Map{
-    pub fn new(width: u32, height: u32) -> Map{
+    pub fn new(width: u32, height: u32, biome: Biome, seed: u64) -> Map{
         Map{
-            biomes: Vec::new(),
+            biomes: vec![vec![biome; width as usize]; height as usize],
             locations: HashMap::new(),
-            width: width,
-            height: height,
+            width,
+            height,
+            seed,
+        }
+    }
+
+    pub fn width(&self) -> u32{
+        self.width
+    }
+
+    pub fn height(&self) -> u32{
+        self.height
+    }
+
+    pub fn seed(&self) -> u64{
+        self.seed
+    }
+
+    pub fn add_location(&mut self, location: Location, local_map: LocalMap){
+        self.locations.insert(location, local_map);
+    }
+
+    pub fn locations(&self) -> impl Iterator<Item = &Location>{
+        self.locations.keys()
+    }
+
+    pub fn local_map(&self, location: &Location) -> Option<&LocalMap>{
+        self.locations.get(location)
+    }
+
+    pub fn local_map_mut(&mut self, location: &Location) -> Option<&mut LocalMap>{
+        self.locations.get_mut(location)
+    }
+
+    pub fn biome_at(&self, x: u32, y: u32) -> Option<Biome>{
+        self.biomes.get(y as usize)?.get(x as usize).copied()
+    }
+
+    pub fn set_biome(&mut self, x: u32, y: u32, biome: Biome){
+        self.biomes[y as usize][x as usize] = biome;
+    }
+
+    // None if (x, y) is outside the map
+    pub fn weather_at(&self, x: u32, y: u32, clock: &WorldClock) -> Option<Weather>{
+        Some(weather_at(self.seed, self.biome_at(x, y)?, x, y, clock))
+    }
+
+    pub fn wind_at(&self, x: u32, y: u32, clock: &WorldClock) -> Option<Wind>{
+        self.biome_at(x, y)?;
+        Some(wind_at(self.seed, x, y, clock))
+    }
+}
+
+
+impl Location{
+    pub fn new(name: String, description: String, x: u32, y: u32) -> Location{
+        Location{
+            name,
+            description,
+            x,
+            y,
         }
     }
-}
\ No newline at end of file
+}
+
+
+impl LocalMap{
+    pub fn new(width: u32, height: u32, tile_type: TileType) -> LocalMap{
+        LocalMap{
+            tiles: (0..height).map(|_| (0..width).map(|_| Tile::new(tile_type)).collect()).collect(),
+            width,
+            height,
+            id: NEXT_MAP_ID.fetch_add(1, Ordering::Relaxed),
+            revision: 0,
+            lights: Vec::new(),
+        }
+    }
+
+    pub fn width(&self) -> u32{
+        self.width
+    }
+
+    pub fn height(&self) -> u32{
+        self.height
+    }
+
+    pub fn id(&self) -> u64{
+        self.id
+    }
+
+    pub fn revision(&self) -> u64{
+        self.revision
+    }
+
+    pub fn contains(&self, position: Coordinates) -> bool{
+        position.x < self.width && position.y < self.height
+    }
+
+    pub fn get_tile(&self, position: Coordinates) -> Option<&Tile>{
+        self.tiles.get(position.y as usize)?.get(position.x as usize)
+    }
+
+    pub fn set_tile(&mut self, position: Coordinates, tile: Tile){
+        self.tiles[position.y as usize][position.x as usize] = tile;
+        self.revision += 1;
+    }
+
+    pub fn add_object(&mut self, position: Coordinates, object: WorldObject){
+        self.tiles[position.y as usize][position.x as usize].objects.push(object);
+        self.revision += 1;
+    }
+
+    // first object of the given type on the tile
+    pub fn world_object_mut(&mut self, position: Coordinates, object_type: WorldObjectType) -> Option<&mut WorldObject>{
+        self.tiles.get_mut(position.y as usize)?
+            .get_mut(position.x as usize)?
+            .objects
+            .iter_mut()
+            .find(|world_object| world_object.object_type == object_type)
+    }
+
+    // removes the first object of the given type from the tile
+    pub fn remove_object(&mut self, position: Coordinates, object_type: WorldObjectType) -> Option<WorldObject>{
+        let objects = &mut self.tiles.get_mut(position.y as usize)?.get_mut(position.x as usize)?.objects;
+        let index = objects.iter().position(|world_object| world_object.object_type == object_type)?;
+        self.revision += 1;
+        Some(objects.remove(index))
+    }
+
+    // the door on the tile, the map counts as changed since the door could be opened or closed
+    pub fn door_mut(&mut self, position: Coordinates) -> Option<&mut Door>{
+        let tile = self.tiles.get_mut(position.y as usize)?.get_mut(position.x as usize)?;
+        match &mut tile.structure{
+            Some(Structure::Door(door)) => {
+                self.revision += 1;
+                Some(door)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn add_light(&mut self, light: LightSource){
+        self.lights.push(light);
+    }
+
+    pub fn lights(&self) -> &[LightSource]{
+        &self.lights
+    }
+
+    // cost of walking onto the tile, None if the tile is outside the map or can't be walked on
+    pub fn movement_cost(&self, position: Coordinates) -> Option<u32>{
+        self.get_tile(position)?.movement_cost()
+    }
+
+    // how much the tile blocks sight, tiles outside the map block it completely
+    pub fn opacity(&self, position: Coordinates) -> f32{
+        self.get_tile(position).map_or(1.0, |tile| tile.opacity())
+    }
+
+    // None if the tile is outside the map
+    pub fn sound_damping(&self, position: Coordinates) -> Option<f32>{
+        Some(self.get_tile(position)?.sound_damping())
+    }
+}
+
+
+impl Tile{
+    pub fn new(tile_type: TileType) -> Tile{
+        Tile{
+            tile_type,
+            objects: Vec::new(),
+            structure: None,
+        }
+    }
+
+    pub fn opacity(&self) -> f32{
+        let structure = self.structure.as_ref().map_or(0.0, |structure| structure.opacity());
+        let objects: f32 = self.objects.iter().map(|world_object| world_object.object_type.opacity()).sum();
+        (structure + objects).min(1.0)
+    }
+
+    pub fn sound_damping(&self) -> f32{
+        let structure = self.structure.as_ref().map_or(0.0, |structure| structure.sound_damping());
+        let objects: f32 = self.objects.iter().map(|world_object| world_object.object_type.sound_damping()).sum();
+        structure + objects
+    }
+
+    pub fn movement_cost(&self) -> Option<u32>{
+        let structure = match &self.structure{
+            Some(structure) => structure.movement_cost()?,
+            None => 0,
+        };
+        self.objects.iter().try_fold(self.tile_type.movement_cost() + structure, |cost, world_object| {
+            Some(cost + world_object.object_type.movement_cost()?)
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::object::Object;
+
+    use super::*;
+
+    #[test]
+    fn test_local_map_new() {
+        let map = LocalMap::new(4, 3, TileType::Grass);
+        assert_eq!(map.width(), 4);
+        assert_eq!(map.height(), 3);
+        assert!(map.get_tile(Coordinates::new(3, 2)).is_some());
+        assert!(map.get_tile(Coordinates::new(4, 2)).is_none());
+    }
+
+    #[test]
+    fn test_local_map_revision() {
+        let mut map = LocalMap::new(4, 3, TileType::Grass);
+        let revision = map.revision();
+        map.set_tile(Coordinates::new(1, 1), Tile::new(TileType::Ice));
+        assert!(map.revision() > revision);
+        assert_eq!(map.get_tile(Coordinates::new(1, 1)).unwrap().tile_type, TileType::Ice);
+    }
+
+    #[test]
+    fn test_map_weather() {
+        let mut map = Map::new(4, 4, Biome::Plains, 42);
+        map.set_biome(1, 1, Biome::LavaOcean);
+        assert_eq!(map.biome_at(1, 1), Some(Biome::LavaOcean));
+        let clock = WorldClock::at(3, 12, 0);
+        assert!(matches!(map.weather_at(1, 1, &clock), Some(Weather::Clear | Weather::HeatWave)));
+        assert_eq!(map.weather_at(4, 0, &clock), None);
+    }
+
+    #[test]
+    fn test_tile_movement_cost() {
+        let mut map = LocalMap::new(2, 1, TileType::Sand);
+        let rock = Object::new(1, String::from("Rock"), String::from("A big rock"), 500, 200);
+        let bush = Object::new(2, String::from("Bush"), String::from("A thorny bush"), 5, 50);
+        map.add_object(Coordinates::new(0, 0), WorldObject::new(rock, WorldObjectType::Rock));
+        map.add_object(Coordinates::new(1, 0), WorldObject::new(bush, WorldObjectType::Bush));
+        assert_eq!(map.movement_cost(Coordinates::new(0, 0)), None);
+        assert_eq!(map.movement_cost(Coordinates::new(1, 0)), Some(25));
+    }
+}
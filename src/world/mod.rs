@@ -0,0 +1,14 @@
+pub mod buildings;
+pub mod environment;
+pub mod fov;
+pub mod harvesting;
+pub mod lighting;
+pub mod map;
+pub mod pathfinding;
+pub mod physics;
+pub mod scent;
+pub mod sound;
+pub mod time;
+pub mod travel;
+pub mod weather;
+pub mod world_elements;
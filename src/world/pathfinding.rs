@@ -0,0 +1,373 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::common::position::Coordinates;
+use crate::world::map::LocalMap;
+use crate::world::weather::Weather;
+
+// diagonal steps cost ~sqrt(2) times an orthogonal one
+const DIAGONAL_COST_NUMERATOR: u32 = 14;
+const DIAGONAL_COST_DENOMINATOR: u32 = 10;
+
+// lowest cost of entering a tile, used to keep the heuristic admissible
+const MIN_TILE_COST: u32 = 10;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Movement{
+    Orthogonal,
+    Diagonal,   // orthogonal and diagonal moves, diagonals can't cut corners of blocked tiles
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path{
+    pub steps: Vec<Coordinates>,    // from start to goal, both included
+    pub cost: u32,
+}
+
+// costs towards a single goal from every tile of the map, useful when many agents are heading to the same place
+pub struct FlowField{
+    goal: Coordinates,
+    movement: Movement,
+    width: u32,
+    costs: Vec<Option<u32>>,
+}
+
+// remembers paths and flow fields until the tiles of the map change or it's used on another map
+pub struct PathCache{
+    map: Option<(u64, u64)>,    // id and revision of the map the entries were computed on
+    paths: HashMap<(Coordinates, Coordinates, Movement), Option<Path>>,
+    flow_fields: HashMap<(Coordinates, Movement), FlowField>,
+}
+
+
+impl Path{
+    // cost of walking the path in some weather, the whole local map has the same weather so the path stays the cheapest
+    pub fn cost_in(&self, weather: Weather) -> u32{
+        weather.effects().movement(self.cost as u64) as u32
+    }
+}
+
+
+// A* from start to goal, None if the goal can't be reached
+pub fn find_path(map: &LocalMap, start: Coordinates, goal: Coordinates, movement: Movement) -> Option<Path>{
+    if !map.contains(start) || map.movement_cost(goal).is_none(){
+        return None;
+    }
+
+    let index = |position: Coordinates| (position.y * map.width() + position.x) as usize;
+    let n_tiles = (map.width() * map.height()) as usize;
+    let mut best_costs = vec![u32::MAX; n_tiles];
+    let mut came_from: Vec<Option<Coordinates>> = vec![None; n_tiles];
+    let mut open = BinaryHeap::new();
+
+    best_costs[index(start)] = 0;
+    open.push(Reverse((heuristic(start, goal, movement), 0, start.x, start.y)));
+
+    while let Some(Reverse((_, cost, x, y))) = open.pop(){
+        let current = Coordinates::new(x, y);
+        if current == goal{
+            let mut steps = vec![goal];
+            let mut position = goal;
+            while let Some(previous) = came_from[index(position)]{
+                steps.push(previous);
+                position = previous;
+            }
+            steps.reverse();
+            return Some(Path{ steps, cost });
+        }
+        if cost > best_costs[index(current)]{
+            continue;
+        }
+
+        for (neighbour, step_cost) in neighbours(map, current, movement){
+            let new_cost = cost + step_cost;
+            if new_cost < best_costs[index(neighbour)]{
+                best_costs[index(neighbour)] = new_cost;
+                came_from[index(neighbour)] = Some(current);
+                open.push(Reverse((new_cost + heuristic(neighbour, goal, movement), new_cost, neighbour.x, neighbour.y)));
+            }
+        }
+    }
+    None
+}
+
+
+// tiles reachable in one step from position, with the cost of moving there
+fn neighbours(map: &LocalMap, position: Coordinates, movement: Movement) -> Vec<(Coordinates, u32)>{
+    let offset = |dx: i64, dy: i64| -> Option<Coordinates>{
+        let x = u32::try_from(position.x as i64 + dx).ok()?;
+        let y = u32::try_from(position.y as i64 + dy).ok()?;
+        let neighbour = Coordinates::new(x, y);
+        map.contains(neighbour).then_some(neighbour)
+    };
+    let passable = |dx: i64, dy: i64| offset(dx, dy).and_then(|neighbour| map.movement_cost(neighbour)).is_some();
+
+    let mut result = Vec::with_capacity(8);
+    for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)]{
+        if let Some(neighbour) = offset(dx, dy){
+            if let Some(cost) = map.movement_cost(neighbour){
+                result.push((neighbour, cost));
+            }
+        }
+    }
+    if movement == Movement::Diagonal{
+        for (dx, dy) in [(1, -1), (1, 1), (-1, 1), (-1, -1)]{
+            if !passable(dx, 0) || !passable(0, dy){
+                continue;
+            }
+            if let Some(neighbour) = offset(dx, dy){
+                if let Some(cost) = map.movement_cost(neighbour){
+                    result.push((neighbour, cost * DIAGONAL_COST_NUMERATOR / DIAGONAL_COST_DENOMINATOR));
+                }
+            }
+        }
+    }
+    result
+}
+
+
+fn heuristic(from: Coordinates, to: Coordinates, movement: Movement) -> u32{
+    match movement{
+        Movement::Orthogonal => from.manhattan_distance(to) * MIN_TILE_COST,
+        Movement::Diagonal => {
+            let dx = from.x.abs_diff(to.x);
+            let dy = from.y.abs_diff(to.y);
+            let diagonal_steps = dx.min(dy);
+            let straight_steps = dx.max(dy) - diagonal_steps;
+            straight_steps * MIN_TILE_COST
+                + diagonal_steps * MIN_TILE_COST * DIAGONAL_COST_NUMERATOR / DIAGONAL_COST_DENOMINATOR
+        }
+    }
+}
+
+
+impl FlowField{
+    // Dijkstra from the goal outwards, moves are symmetric so the cost to reach the goal
+    // from a tile is the cost of entering the goal's side of every step on the way back
+    pub fn new(map: &LocalMap, goal: Coordinates, movement: Movement) -> FlowField{
+        let width = map.width();
+        let index = |position: Coordinates| (position.y * width + position.x) as usize;
+        let mut costs = vec![None; (map.width() * map.height()) as usize];
+        let mut open = BinaryHeap::new();
+
+        if map.movement_cost(goal).is_some(){
+            costs[index(goal)] = Some(0);
+            open.push(Reverse((0, goal.x, goal.y)));
+        }
+
+        while let Some(Reverse((cost, x, y))) = open.pop(){
+            let current = Coordinates::new(x, y);
+            if costs[index(current)].is_some_and(|best| cost > best){
+                continue;
+            }
+            // walking from neighbour to current costs as much as entering current
+            let enter_current = map.movement_cost(current).unwrap_or(0);
+            for (neighbour, _) in neighbours(map, current, movement){
+                let reverse_step_cost = if neighbour.x != current.x && neighbour.y != current.y{
+                    enter_current * DIAGONAL_COST_NUMERATOR / DIAGONAL_COST_DENOMINATOR
+                }
+                else{
+                    enter_current
+                };
+                let new_cost = cost + reverse_step_cost;
+                if costs[index(neighbour)].is_none_or(|best| new_cost < best){
+                    costs[index(neighbour)] = Some(new_cost);
+                    open.push(Reverse((new_cost, neighbour.x, neighbour.y)));
+                }
+            }
+        }
+
+        FlowField{
+            goal,
+            movement,
+            width,
+            costs,
+        }
+    }
+
+    pub fn goal(&self) -> Coordinates{
+        self.goal
+    }
+
+    // cost of reaching the goal from position, None if it can't be reached
+    pub fn cost_to_goal(&self, position: Coordinates) -> Option<u32>{
+        if position.x >= self.width{
+            return None;
+        }
+        *self.costs.get((position.y * self.width + position.x) as usize)?
+    }
+
+    // best tile to move to from position to get closer to the goal
+    pub fn next_step(&self, map: &LocalMap, position: Coordinates) -> Option<Coordinates>{
+        if position == self.goal{
+            return None;
+        }
+        let own_cost = self.cost_to_goal(position)?;
+        neighbours(map, position, self.movement)
+            .into_iter()
+            .filter_map(|(neighbour, step_cost)| Some((neighbour, self.cost_to_goal(neighbour)? + step_cost)))
+            .filter(|(_, total)| *total <= own_cost)
+            .min_by_key(|(_, total)| *total)
+            .map(|(neighbour, _)| neighbour)
+    }
+}
+
+
+impl PathCache{
+    pub fn new() -> PathCache{
+        PathCache{
+            map: None,
+            paths: HashMap::new(),
+            flow_fields: HashMap::new(),
+        }
+    }
+
+    pub fn find_path(&mut self, map: &LocalMap, start: Coordinates, goal: Coordinates, movement: Movement) -> Option<Path>{
+        self.invalidate_if_changed(map);
+        self.paths
+            .entry((start, goal, movement))
+            .or_insert_with(|| find_path(map, start, goal, movement))
+            .clone()
+    }
+
+    pub fn flow_field(&mut self, map: &LocalMap, goal: Coordinates, movement: Movement) -> &FlowField{
+        self.invalidate_if_changed(map);
+        self.flow_fields
+            .entry((goal, movement))
+            .or_insert_with(|| FlowField::new(map, goal, movement))
+    }
+
+    fn invalidate_if_changed(&mut self, map: &LocalMap){
+        let current = Some((map.id(), map.revision()));
+        if self.map != current{
+            self.paths.clear();
+            self.flow_fields.clear();
+            self.map = current;
+        }
+    }
+}
+
+
+impl Default for PathCache{
+    fn default() -> PathCache{
+        PathCache::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::object::Object;
+    use crate::world::map::Tile;
+    use crate::world::world_elements::{TileType, WorldObject, WorldObjectType};
+
+    use super::*;
+
+    // vertical wall at x = 2 with a single gap at the bottom
+    fn walled_map() -> LocalMap{
+        let mut map = LocalMap::new(5, 5, TileType::Grass);
+        for y in 0..4{
+            map.add_object(Coordinates::new(2, y), rock());
+        }
+        map
+    }
+
+    fn rock() -> WorldObject{
+        WorldObject::new(Object::new(1, String::from("Rock"), String::from("A big rock"), 500, 200), WorldObjectType::Rock)
+    }
+
+    fn bush() -> WorldObject{
+        WorldObject::new(Object::new(2, String::from("Bush"), String::from("A thorny bush"), 5, 50), WorldObjectType::Bush)
+    }
+
+    #[test]
+    fn test_find_path_straight() {
+        let map = LocalMap::new(5, 5, TileType::Grass);
+        let path = find_path(&map, Coordinates::new(0, 0), Coordinates::new(4, 0), Movement::Orthogonal).unwrap();
+        assert_eq!(path.steps.len(), 5);
+        assert_eq!(path.cost, 40);
+        assert_eq!(path.cost_in(Weather::Clear), 40);
+        assert_eq!(path.cost_in(Weather::Snow), 60);
+    }
+
+    #[test]
+    fn test_find_path_around_wall() {
+        let map = walled_map();
+        let path = find_path(&map, Coordinates::new(0, 0), Coordinates::new(4, 0), Movement::Orthogonal).unwrap();
+        assert!(path.steps.contains(&Coordinates::new(2, 4)));
+        assert_eq!(path.steps.first(), Some(&Coordinates::new(0, 0)));
+        assert_eq!(path.steps.last(), Some(&Coordinates::new(4, 0)));
+    }
+
+    #[test]
+    fn test_find_path_diagonal() {
+        let map = LocalMap::new(5, 5, TileType::Grass);
+        let path = find_path(&map, Coordinates::new(0, 0), Coordinates::new(4, 4), Movement::Diagonal).unwrap();
+        assert_eq!(path.steps.len(), 5);
+        assert_eq!(path.cost, 56);
+    }
+
+    #[test]
+    fn test_find_path_prefers_cheap_terrain() {
+        let mut map = LocalMap::new(3, 3, TileType::Grass);
+        map.set_tile(Coordinates::new(1, 0), Tile::new(TileType::Ice));
+        map.add_object(Coordinates::new(1, 0), bush());
+        let path = find_path(&map, Coordinates::new(0, 0), Coordinates::new(2, 0), Movement::Orthogonal).unwrap();
+        assert!(!path.steps.contains(&Coordinates::new(1, 0)));
+        assert_eq!(path.cost, 40);
+    }
+
+    #[test]
+    fn test_find_path_unreachable() {
+        let mut map = LocalMap::new(3, 3, TileType::Grass);
+        for y in 0..3{
+            map.add_object(Coordinates::new(1, y), rock());
+        }
+        assert!(find_path(&map, Coordinates::new(0, 0), Coordinates::new(2, 2), Movement::Diagonal).is_none());
+    }
+
+    #[test]
+    fn test_flow_field() {
+        let map = walled_map();
+        let goal = Coordinates::new(4, 0);
+        let flow_field = FlowField::new(&map, goal, Movement::Orthogonal);
+        let start = Coordinates::new(0, 0);
+        let path = find_path(&map, start, goal, Movement::Orthogonal).unwrap();
+        assert_eq!(flow_field.cost_to_goal(start), Some(path.cost));
+
+        let mut position = start;
+        let mut n_steps = 0;
+        while let Some(next) = flow_field.next_step(&map, position){
+            position = next;
+            n_steps += 1;
+        }
+        assert_eq!(position, goal);
+        assert_eq!(n_steps, path.steps.len() - 1);
+    }
+
+    #[test]
+    fn test_path_cache_invalidation() {
+        let mut map = LocalMap::new(3, 1, TileType::Grass);
+        let mut cache = PathCache::new();
+        let (start, goal) = (Coordinates::new(0, 0), Coordinates::new(2, 0));
+        assert!(cache.find_path(&map, start, goal, Movement::Orthogonal).is_some());
+
+        map.add_object(Coordinates::new(1, 0), rock());
+        assert!(cache.find_path(&map, start, goal, Movement::Orthogonal).is_none());
+        assert_eq!(cache.flow_field(&map, goal, Movement::Orthogonal).cost_to_goal(start), None);
+    }
+
+    #[test]
+    fn test_path_cache_on_another_map() {
+        let mut walled = LocalMap::new(3, 1, TileType::Grass);
+        walled.add_object(Coordinates::new(1, 0), rock());
+        let mut open = LocalMap::new(3, 1, TileType::Grass);
+        open.add_object(Coordinates::new(1, 0), bush());
+        assert_eq!(walled.revision(), open.revision());
+        let mut cache = PathCache::new();
+        let (start, goal) = (Coordinates::new(0, 0), Coordinates::new(2, 0));
+        assert!(cache.find_path(&walled, start, goal, Movement::Orthogonal).is_none());
+        assert!(cache.find_path(&open, start, goal, Movement::Orthogonal).is_some());
+    }
+}
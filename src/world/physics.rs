@@ -0,0 +1,170 @@
+use crate::actors::actor::Actor;
+use crate::actors::progression::Practice;
+use crate::common::stats::{Stats, SubStat};
+use crate::common::status::StatusType;
+use crate::world::world_elements::World;
+
+// temperatures past which the environment alone burns or freezes an unprotected actor
+const BURN_TEMPERATURE: f32 = 50.0;
+const FREEZE_TEMPERATURE: f32 = -30.0;
+
+// kg an actor can carry on earth for every level of its carry weight
+const KG_PER_CARRY_LEVEL: f32 = 10.0;
+
+// below this gravity is treated as this, to avoid infinite jumps and ranges
+const MIN_GRAVITY: f32 = 0.05;
+
+
+// physical properties of a world, everything is relative to earth except the temperature
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldParameters{
+    pub gravity: f32,
+    pub atmosphere: f32,            // density of the air, 0 is vacuum
+    pub ambient_temperature: f32,   // celsius
+    pub breathable: bool,
+}
+
+
+impl World{
+    pub fn parameters(&self) -> WorldParameters{
+        match self{
+            World::Earth => WorldParameters::new(1.0, 1.0, 15.0, true),
+            World::Mars => WorldParameters::new(0.38, 0.01, -60.0, false),
+            World::Venus => WorldParameters::new(0.9, 90.0, 460.0, false),
+            World::Mercury => WorldParameters::new(0.38, 0.0, 170.0, false),
+            World::Moon => WorldParameters::new(0.17, 0.0, -20.0, false),
+            World::Europa => WorldParameters::new(0.13, 0.0, -160.0, false),
+            World::Titan => WorldParameters::new(0.14, 1.45, -180.0, false),
+            World::Hell => WorldParameters::new(1.2, 1.5, 80.0, true),
+            World::Heaven => WorldParameters::new(0.8, 0.8, 20.0, true),
+            World::Purgatory => WorldParameters::new(1.0, 1.0, 10.0, true),
+        }
+    }
+}
+
+
+impl WorldParameters{
+    pub fn new(gravity: f32, atmosphere: f32, ambient_temperature: f32, breathable: bool) -> WorldParameters{
+        WorldParameters{
+            gravity,
+            atmosphere,
+            ambient_temperature,
+            breathable,
+        }
+    }
+
+    // lower gravity makes it easier to jump and climb, 1 on earth
+    pub fn gravity_modifier(&self) -> f32{
+        1.0 / self.gravity.max(MIN_GRAVITY)
+    }
+
+    // projectiles fly further with lower gravity and thinner air, 1 on earth
+    pub fn projectile_range_modifier(&self) -> f32{
+        let drag = 1.1 / (1.0 + 0.1 * self.atmosphere);
+        self.gravity_modifier() * drag
+    }
+
+    // true if a jump of the given difficulty succeeds
+    pub fn jump_check(&self, stats: &Stats, difficulty: f32) -> bool{
+        let skill = (stats.agility.parkour + stats.agility.acrobatics) / 2.0;
+        skill * self.gravity_modifier() >= difficulty
+    }
+
+    // true if a climb of the given difficulty succeeds
+    pub fn climb_check(&self, stats: &Stats, difficulty: f32) -> bool{
+        stats.agility.climbing * self.gravity_modifier() >= difficulty
+    }
+
+    // makes a jump, practicing both parkour and acrobatics
+    pub fn jump(&self, stats: &Stats, difficulty: f32) -> [Practice; 2]{
+        let succeeded = self.jump_check(stats, difficulty);
+        [SubStat::Parkour, SubStat::Acrobatics].map(|sub_stat| Practice::new(sub_stat, difficulty, succeeded))
+    }
+
+    pub fn climb(&self, stats: &Stats, difficulty: f32) -> Practice{
+        Practice::new(SubStat::Climbing, difficulty, self.climb_check(stats, difficulty))
+    }
+
+    // in kg, the carry weight stat is a level
+    pub fn max_carry_weight(&self, stats: &Stats) -> u32{
+        (stats.strength.max_carry_weight as f32 * KG_PER_CARRY_LEVEL * self.gravity_modifier()) as u32
+    }
+
+    // statuses the environment inflicts on anyone that isn't protected from them
+    pub fn environmental_statuses(&self) -> Vec<StatusType>{
+        let mut statuses = Vec::new();
+        if !self.breathable{
+            statuses.push(StatusType::Suffocating);
+        }
+        if self.ambient_temperature >= BURN_TEMPERATURE{
+            statuses.push(StatusType::Burn);
+        }
+        if self.ambient_temperature <= FREEZE_TEMPERATURE{
+            statuses.push(StatusType::Freeze);
+        }
+        statuses
+    }
+
+    // inflicts the environmental statuses for one tick, called every tick the actor spends in this world
+    pub fn apply_environment(&self, actor: &mut Actor){
+        for status in self.environmental_statuses(){
+            if !is_protected(actor, status){
+                actor.add_status(status, 1);
+            }
+        }
+    }
+}
+
+
+// true if the actor has a status that protects it from the environmental status
+pub fn is_protected(actor: &Actor, status: StatusType) -> bool{
+    let protection = match status{
+        StatusType::Suffocating => StatusType::Airtight,
+        StatusType::Burn => StatusType::HeatResistance,
+        StatusType::Freeze => StatusType::ColdResistance,
+        _ => return actor.has_status(StatusType::Invincible),
+    };
+    actor.has_status(protection) || actor.has_status(StatusType::Invincible)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::actor::Body;
+
+    use super::*;
+
+    #[test]
+    fn test_earth_is_neutral() {
+        let earth = World::Earth.parameters();
+        assert_eq!(earth.gravity_modifier(), 1.0);
+        assert_eq!(earth.projectile_range_modifier(), 1.0);
+        assert!(earth.environmental_statuses().is_empty());
+    }
+
+    #[test]
+    fn test_low_gravity_checks() {
+        let mut stats = Stats::new_zero();
+        stats.agility.climbing = 0.3;
+        stats.strength.max_carry_weight = 10;
+        let earth = World::Earth.parameters();
+        let moon = World::Moon.parameters();
+        assert!(!earth.climb_check(&stats, 0.5));
+        assert!(moon.climb_check(&stats, 0.5));
+        assert_eq!(earth.climb(&stats, 0.5), Practice::new(SubStat::Climbing, 0.5, false));
+        assert!(moon.jump(&stats, 0.5).iter().all(|practice| !practice.succeeded));
+        assert_eq!(earth.max_carry_weight(&stats), 100);
+        assert!(moon.max_carry_weight(&stats) > earth.max_carry_weight(&stats));
+        assert!(moon.projectile_range_modifier() > earth.projectile_range_modifier());
+    }
+
+    #[test]
+    fn test_apply_environment() {
+        let europa = World::Europa.parameters();
+        let mut actor = Actor::new(Body::new(vec![], 180, 70), Stats::new_zero());
+        actor.add_status(StatusType::Airtight, 100);
+        europa.apply_environment(&mut actor);
+        assert!(!actor.has_status(StatusType::Suffocating));
+        assert!(actor.has_status(StatusType::Freeze));
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use crate::actors::actor::{Actor, ActorId};
+use crate::common::position::Coordinates;
+use crate::world::map::LocalMap;
+use crate::world::weather::Weather;
+use crate::world::world_elements::TileType;
+
+// scent left on a tile by an actor standing on it for a tick
+const SCENT_DEPOSIT: f32 = 10.0;
+
+// fraction of the scent lost every tick in clear weather
+const BASE_DECAY_RATE: f32 = 0.01;
+
+// scents weaker than this are forgotten
+const MIN_SCENT: f32 = 0.01;
+
+// a tracker can follow a scent if its strength times the tracking sense is at least this much
+const TRACKING_THRESHOLD: f32 = 0.5;
+
+
+// scents left by actors on the tiles of a local map
+pub struct ScentMap{
+    scents: HashMap<Coordinates, HashMap<ActorId, f32>>,
+}
+
+
+impl Weather{
+    // how much faster scents fade in this weather
+    pub fn scent_decay_multiplier(&self) -> f32{
+        match self{
+            Weather::Clear => 1.0,
+            Weather::Fog => 0.8,
+            Weather::HeatWave => 1.5,
+            Weather::Snow => 3.0,
+            Weather::Rain => 4.0,
+            Weather::Sandstorm => 5.0,
+        }
+    }
+}
+
+
+// smell, helped a bit by taste like an animal licking the ground
+pub fn tracking_sense(tracker: &Actor) -> f32{
+    let perception = tracker.get_stats().perception;
+    perception.smell + 0.25 * perception.taste
+}
+
+
+pub fn can_smell(tracker: &Actor, scent: f32) -> bool{
+    scent * tracking_sense(tracker) >= TRACKING_THRESHOLD
+}
+
+
+impl ScentMap{
+    pub fn new() -> ScentMap{
+        ScentMap{
+            scents: HashMap::new(),
+        }
+    }
+
+    // called every tick for every actor, water doesn't hold any scent
+    pub fn leave_scent(&mut self, map: &LocalMap, actor: ActorId, position: Coordinates){
+        if map.get_tile(position).is_none_or(|tile| tile.tile_type == TileType::Water){
+            return;
+        }
+        let scent = self.scents.entry(position).or_default().entry(actor).or_insert(0.0);
+        *scent = scent.max(SCENT_DEPOSIT);
+    }
+
+    pub fn scent_at(&self, position: Coordinates, actor: ActorId) -> f32{
+        self.scents.get(&position).and_then(|scents| scents.get(&actor)).copied().unwrap_or(0.0)
+    }
+
+    // a tick of fading, faster in bad weather, and whatever ended up in water is washed away
+    pub fn decay(&mut self, map: &LocalMap, weather: Weather){
+        let kept = (1.0 - BASE_DECAY_RATE * weather.scent_decay_multiplier()).max(0.0);
+        self.scents.retain(|position, scents| {
+            if map.get_tile(*position).is_none_or(|tile| tile.tile_type == TileType::Water){
+                return false;
+            }
+            scents.retain(|_, scent| {
+                *scent *= kept;
+                *scent >= MIN_SCENT
+            });
+            !scents.is_empty()
+        });
+    }
+
+    // next tile to follow the trail of the target, towards where the scent is fresher,
+    // None if the tracker lost the trail or is standing on the freshest scent around
+    pub fn follow_trail(&self, map: &LocalMap, tracker: &Actor, target: ActorId, from: Coordinates) -> Option<Coordinates>{
+        let current = self.scent_at(from, target);
+        let mut best: Option<(Coordinates, f32)> = None;
+        for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0), (1, -1), (1, 1), (-1, 1), (-1, -1)]{
+            let (x, y) = (from.x as i64 + dx, from.y as i64 + dy);
+            if x < 0 || y < 0{
+                continue;
+            }
+            let neighbour = Coordinates::new(x as u32, y as u32);
+            let scent = self.scent_at(neighbour, target);
+            if map.movement_cost(neighbour).is_none() || !can_smell(tracker, scent) || scent <= current{
+                continue;
+            }
+            if best.is_none_or(|(_, best_scent)| scent > best_scent){
+                best = Some((neighbour, scent));
+            }
+        }
+        best.map(|(position, _)| position)
+    }
+}
+
+
+impl Default for ScentMap{
+    fn default() -> ScentMap{
+        ScentMap::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::actor::Body;
+    use crate::common::stats::Stats;
+    use crate::world::map::Tile;
+
+    use super::*;
+
+    fn tracker(smell: f32) -> Actor{
+        let mut stats = Stats::new_zero();
+        stats.perception.smell = smell;
+        Actor::new(Body::new(vec![], 80, 40), stats)
+    }
+
+    // the target walked from (0, 0) to (9, 0), one tile per tick
+    fn trail(map: &LocalMap) -> ScentMap{
+        let mut scent_map = ScentMap::new();
+        for x in 0..10{
+            scent_map.decay(map, Weather::Clear);
+            scent_map.leave_scent(map, 1, Coordinates::new(x, 0));
+        }
+        scent_map
+    }
+
+    #[test]
+    fn test_follow_trail() {
+        let map = LocalMap::new(10, 3, TileType::Grass);
+        let scent_map = trail(&map);
+        let hound = tracker(0.9);
+        let mut position = Coordinates::new(0, 0);
+        while let Some(next) = scent_map.follow_trail(&map, &hound, 1, position){
+            position = next;
+        }
+        assert_eq!(position, Coordinates::new(9, 0));
+        assert!(scent_map.follow_trail(&map, &hound, 2, Coordinates::new(0, 0)).is_none());
+    }
+
+    #[test]
+    fn test_weak_nose_loses_old_trail() {
+        let map = LocalMap::new(10, 3, TileType::Grass);
+        let mut scent_map = trail(&map);
+        for _ in 0..30{
+            scent_map.decay(&map, Weather::Rain);
+        }
+        assert!(scent_map.follow_trail(&map, &tracker(0.1), 1, Coordinates::new(0, 0)).is_none());
+        assert!(scent_map.follow_trail(&map, &tracker(1.0), 1, Coordinates::new(0, 0)).is_some());
+    }
+
+    #[test]
+    fn test_rain_washes_scent_faster() {
+        let map = LocalMap::new(2, 1, TileType::Grass);
+        let (mut clear, mut rainy) = (ScentMap::new(), ScentMap::new());
+        clear.leave_scent(&map, 1, Coordinates::new(0, 0));
+        rainy.leave_scent(&map, 1, Coordinates::new(0, 0));
+        clear.decay(&map, Weather::Clear);
+        rainy.decay(&map, Weather::Rain);
+        assert!(rainy.scent_at(Coordinates::new(0, 0), 1) < clear.scent_at(Coordinates::new(0, 0), 1));
+    }
+
+    #[test]
+    fn test_water_holds_no_scent() {
+        let mut map = LocalMap::new(3, 1, TileType::Grass);
+        map.set_tile(Coordinates::new(1, 0), Tile::new(TileType::Water));
+        let mut scent_map = ScentMap::new();
+        scent_map.leave_scent(&map, 1, Coordinates::new(1, 0));
+        assert_eq!(scent_map.scent_at(Coordinates::new(1, 0), 1), 0.0);
+    }
+}
@@ -0,0 +1,243 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::actors::actor::{Actor, ActorId};
+use crate::actors::detection::{noise, Pace};
+use crate::common::position::Coordinates;
+use crate::world::map::LocalMap;
+
+// loudness lost for every tile a sound travels, diagonals lose a bit more
+const LOUDNESS_LOST_PER_TILE: f32 = 1.0;
+const LOUDNESS_LOST_PER_DIAGONAL_TILE: f32 = 1.4;
+
+const COMBAT_LOUDNESS: f32 = 25.0;
+const SPEECH_LOUDNESS: f32 = 8.0;
+const SHOUT_LOUDNESS: f32 = 20.0;
+// loudness of the footsteps of someone running with no stealth and no equipment
+const FOOTSTEPS_LOUDNESS: f32 = 12.0;
+
+// a listener notices a sound if its loudness times its hearing is at least this much
+const HEARING_THRESHOLD: f32 = 1.0;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundKind{
+    Combat,
+    Footsteps,
+    Speech,
+    Shout,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sound{
+    pub kind: SoundKind,
+    pub source: Coordinates,
+    pub loudness: f32,
+    pub emitter: Option<ActorId>,
+}
+
+// a sound that reached a listener loudly enough to be noticed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeardSound{
+    pub listener: ActorId,
+    pub sound: Sound,
+    pub loudness: f32,      // loudness when it reached the listener
+}
+
+// loudness of a sound on every tile it reaches
+pub struct SoundField{
+    pub sound: Sound,
+    loudness: HashMap<Coordinates, f32>,
+}
+
+// max-heap entry ordered by the loudness left
+struct Front(f32, Coordinates);
+
+
+impl Sound{
+    pub fn new(kind: SoundKind, source: Coordinates, loudness: f32, emitter: Option<ActorId>) -> Sound{
+        Sound{
+            kind,
+            source,
+            loudness,
+            emitter,
+        }
+    }
+
+    pub fn combat(source: Coordinates, emitter: ActorId) -> Sound{
+        Sound::new(SoundKind::Combat, source, COMBAT_LOUDNESS, Some(emitter))
+    }
+
+    pub fn speech(source: Coordinates, emitter: ActorId) -> Sound{
+        Sound::new(SoundKind::Speech, source, SPEECH_LOUDNESS, Some(emitter))
+    }
+
+    pub fn shout(source: Coordinates, emitter: ActorId) -> Sound{
+        Sound::new(SoundKind::Shout, source, SHOUT_LOUDNESS, Some(emitter))
+    }
+
+    // louder with heavy equipment and quieter with stealth, None if the actor is silent
+    pub fn footsteps(source: Coordinates, emitter: ActorId, actor: &Actor, pace: Pace) -> Option<Sound>{
+        let loudness = FOOTSTEPS_LOUDNESS * noise(actor, pace);
+        (loudness > 0.0).then(|| Sound::new(SoundKind::Footsteps, source, loudness, Some(emitter)))
+    }
+}
+
+
+impl SoundField{
+    // spreads the sound from its source, every tile takes away some loudness and
+    // obstacles take away more, so a sound can go around a wall more easily than through it
+    pub fn new(map: &LocalMap, sound: Sound) -> SoundField{
+        let mut loudness = HashMap::new();
+        let mut front = BinaryHeap::new();
+        if map.contains(sound.source){
+            loudness.insert(sound.source, sound.loudness);
+            front.push(Front(sound.loudness, sound.source));
+        }
+
+        while let Some(Front(level, position)) = front.pop(){
+            if loudness.get(&position).is_some_and(|best| level < *best){
+                continue;
+            }
+            for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0), (1, -1), (1, 1), (-1, 1), (-1, -1)]{
+                let (x, y) = (position.x as i64 + dx, position.y as i64 + dy);
+                if x < 0 || y < 0{
+                    continue;
+                }
+                let neighbour = Coordinates::new(x as u32, y as u32);
+                let Some(damping) = map.sound_damping(neighbour) else{
+                    continue;
+                };
+                let lost = if dx != 0 && dy != 0 { LOUDNESS_LOST_PER_DIAGONAL_TILE } else { LOUDNESS_LOST_PER_TILE };
+                let new_level = level - lost - damping;
+                if new_level > 0.0 && loudness.get(&neighbour).is_none_or(|best| new_level > *best){
+                    loudness.insert(neighbour, new_level);
+                    front.push(Front(new_level, neighbour));
+                }
+            }
+        }
+
+        SoundField{
+            sound,
+            loudness,
+        }
+    }
+
+    // 0 if the sound doesn't reach the tile
+    pub fn loudness_at(&self, position: Coordinates) -> f32{
+        self.loudness.get(&position).copied().unwrap_or(0.0)
+    }
+}
+
+
+pub fn can_hear(listener: &Actor, loudness: f32) -> bool{
+    loudness * listener.get_stats().perception.hearing >= HEARING_THRESHOLD
+}
+
+
+// every sound each listener notices during a tick, a listener doesn't hear its own sounds
+pub fn listen(map: &LocalMap, sounds: &[Sound], listeners: &[(ActorId, &Actor, Coordinates)]) -> Vec<HeardSound>{
+    let mut heard = Vec::new();
+    for sound in sounds{
+        let field = SoundField::new(map, *sound);
+        for (listener_id, listener, position) in listeners{
+            if sound.emitter == Some(*listener_id){
+                continue;
+            }
+            let loudness = field.loudness_at(*position);
+            if can_hear(listener, loudness){
+                heard.push(HeardSound{
+                    listener: *listener_id,
+                    sound: *sound,
+                    loudness,
+                });
+            }
+        }
+    }
+    heard
+}
+
+
+impl PartialEq for Front{
+    fn eq(&self, other: &Front) -> bool{
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Front{}
+
+impl PartialOrd for Front{
+    fn partial_cmp(&self, other: &Front) -> Option<Ordering>{
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Front{
+    fn cmp(&self, other: &Front) -> Ordering{
+        self.0.total_cmp(&other.0)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::actor::Body;
+    use crate::common::stats::Stats;
+    use crate::objects::object::Object;
+    use crate::world::world_elements::{TileType, WorldObject, WorldObjectType};
+
+    use super::*;
+
+    fn listener(hearing: f32) -> Actor{
+        let mut stats = Stats::new_zero();
+        stats.perception.hearing = hearing;
+        Actor::new(Body::new(vec![], 180, 70), stats)
+    }
+
+    fn rock() -> WorldObject{
+        WorldObject::new(Object::new(1, String::from("Rock"), String::from("A big rock"), 500, 200), WorldObjectType::Rock)
+    }
+
+    #[test]
+    fn test_sound_fades_with_distance() {
+        let map = LocalMap::new(40, 1, TileType::Grass);
+        let field = SoundField::new(&map, Sound::combat(Coordinates::new(0, 0), 1));
+        assert_eq!(field.loudness_at(Coordinates::new(0, 0)), COMBAT_LOUDNESS);
+        assert_eq!(field.loudness_at(Coordinates::new(5, 0)), COMBAT_LOUDNESS - 5.0);
+        assert_eq!(field.loudness_at(Coordinates::new(30, 0)), 0.0);
+    }
+
+    #[test]
+    fn test_obstacles_muffle_sound() {
+        let mut map = LocalMap::new(10, 1, TileType::Grass);
+        map.add_object(Coordinates::new(2, 0), rock());
+        let field = SoundField::new(&map, Sound::speech(Coordinates::new(0, 0), 1));
+        assert_eq!(field.loudness_at(Coordinates::new(3, 0)), SPEECH_LOUDNESS - 3.0 - 4.0);
+    }
+
+    #[test]
+    fn test_good_hearing_hears_fights_out_of_sight() {
+        let mut map = LocalMap::new(12, 12, TileType::Grass);
+        for y in 0..11{
+            map.add_object(Coordinates::new(5, y), rock());
+        }
+        let sounds = [Sound::combat(Coordinates::new(2, 2), 1)];
+        let (guard, deaf) = (listener(0.8), listener(0.05));
+        let listeners = [(2, &guard, Coordinates::new(8, 2)), (3, &deaf, Coordinates::new(8, 2)), (1, &guard, Coordinates::new(2, 2))];
+        let heard = listen(&map, &sounds, &listeners);
+        assert_eq!(heard.len(), 1);
+        assert_eq!(heard[0].listener, 2);
+        assert_eq!(heard[0].sound.kind, SoundKind::Combat);
+    }
+
+    #[test]
+    fn test_sneaking_is_quiet() {
+        let mut stats = Stats::new_zero();
+        stats.agility.stealth = 1.0;
+        let thief = Actor::new(Body::new(vec![], 170, 60), stats);
+        assert!(Sound::footsteps(Coordinates::new(0, 0), 1, &thief, Pace::Still).is_none());
+        let sneaking = Sound::footsteps(Coordinates::new(0, 0), 1, &thief, Pace::Sneaking).unwrap();
+        let running = Sound::footsteps(Coordinates::new(0, 0), 1, &listener(0.0), Pace::Running).unwrap();
+        assert!(sneaking.loudness < running.loudness / 10.0);
+    }
+}
@@ -0,0 +1,129 @@
+pub const TICKS_PER_HOUR: u64 = 60;
+pub const HOURS_PER_DAY: u64 = 24;
+pub const TICKS_PER_DAY: u64 = TICKS_PER_HOUR * HOURS_PER_DAY;
+pub const DAYS_PER_SEASON: u64 = 30;
+
+// light of the moon and stars, relative to full daylight
+const NIGHT_LIGHT: f32 = 0.05;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DayPhase{
+    Dawn,   // 5 to 7
+    Day,    // 7 to 18
+    Dusk,   // 18 to 20
+    Night,  // 20 to 5
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Season{
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+// counts the ticks since the start of the world, a tick is a minute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorldClock{
+    ticks: u64,
+}
+
+
+impl WorldClock{
+    pub fn new(ticks: u64) -> WorldClock{
+        WorldClock{
+            ticks,
+        }
+    }
+
+    pub fn at(day: u64, hour: u64, minute: u64) -> WorldClock{
+        WorldClock::new(day * TICKS_PER_DAY + hour * TICKS_PER_HOUR + minute)
+    }
+
+    pub fn advance(&mut self, ticks: u64){
+        self.ticks += ticks;
+    }
+
+    pub fn ticks(&self) -> u64{
+        self.ticks
+    }
+
+    pub fn day(&self) -> u64{
+        self.ticks / TICKS_PER_DAY
+    }
+
+    // the world starts on the first day of spring
+    pub fn season(&self) -> Season{
+        match (self.day() / DAYS_PER_SEASON) % 4{
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    // hours since midnight, with the minutes as the fractional part
+    pub fn time_of_day(&self) -> f32{
+        (self.ticks % TICKS_PER_DAY) as f32 / TICKS_PER_HOUR as f32
+    }
+
+    pub fn phase(&self) -> DayPhase{
+        match self.time_of_day(){
+            hour if (5.0..7.0).contains(&hour) => DayPhase::Dawn,
+            hour if (7.0..18.0).contains(&hour) => DayPhase::Day,
+            hour if (18.0..20.0).contains(&hour) => DayPhase::Dusk,
+            _ => DayPhase::Night,
+        }
+    }
+
+    // light from the sky between 0 and 1, fading in at dawn and out at dusk
+    pub fn sunlight(&self) -> f32{
+        let hour = self.time_of_day();
+        match self.phase(){
+            DayPhase::Day => 1.0,
+            DayPhase::Night => NIGHT_LIGHT,
+            DayPhase::Dawn => NIGHT_LIGHT + (1.0 - NIGHT_LIGHT) * (hour - 5.0) / 2.0,
+            DayPhase::Dusk => 1.0 - (1.0 - NIGHT_LIGHT) * (hour - 18.0) / 2.0,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_phases() {
+        assert_eq!(WorldClock::at(0, 3, 0).phase(), DayPhase::Night);
+        assert_eq!(WorldClock::at(0, 6, 0).phase(), DayPhase::Dawn);
+        assert_eq!(WorldClock::at(0, 12, 0).phase(), DayPhase::Day);
+        assert_eq!(WorldClock::at(0, 19, 0).phase(), DayPhase::Dusk);
+        assert_eq!(WorldClock::at(0, 23, 0).phase(), DayPhase::Night);
+    }
+
+    #[test]
+    fn test_clock_advance() {
+        let mut clock = WorldClock::at(0, 23, 30);
+        clock.advance(60);
+        assert_eq!(clock.day(), 1);
+        assert_eq!(clock.time_of_day(), 0.5);
+    }
+
+    #[test]
+    fn test_seasons() {
+        assert_eq!(WorldClock::at(0, 0, 0).season(), Season::Spring);
+        assert_eq!(WorldClock::at(DAYS_PER_SEASON, 0, 0).season(), Season::Summer);
+        assert_eq!(WorldClock::at(DAYS_PER_SEASON * 3 + 1, 0, 0).season(), Season::Winter);
+        assert_eq!(WorldClock::at(DAYS_PER_SEASON * 4, 0, 0).season(), Season::Spring);
+    }
+
+    #[test]
+    fn test_sunlight() {
+        assert_eq!(WorldClock::at(0, 12, 0).sunlight(), 1.0);
+        assert_eq!(WorldClock::at(0, 0, 0).sunlight(), NIGHT_LIGHT);
+        let dawn = WorldClock::at(0, 6, 0).sunlight();
+        assert!(dawn > NIGHT_LIGHT && dawn < 1.0);
+    }
+}
@@ -0,0 +1,373 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use rand::Rng;
+
+use crate::actors::actor::Actor;
+use crate::common::position::Coordinates;
+use crate::world::map::{LocalMap, Location, Map};
+use crate::world::time::WorldClock;
+use crate::world::weather::{movement_stamina, Weather};
+use crate::world::world_elements::Biome;
+
+// sprint speed of an average traveller, faster parties travel proportionally faster
+const REFERENCE_SPEED: u32 = 5;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Edge{
+    North,
+    South,
+    East,
+    West,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EncounterKind{
+    Bandits,
+    Wildlife,
+    Traveller,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Encounter{
+    pub kind: EncounterKind,
+    pub region: (u32, u32),
+    pub clock: WorldClock,
+}
+
+// regions of the world map crossed to go from a location to another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route{
+    pub regions: Vec<(u32, u32)>,   // from the start region to the destination region, both included
+    pub base_time: u64,             // ticks it takes an average party
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Journey{
+    pub encounters: Vec<Encounter>,
+    pub arrival_edge: Edge,     // edge of the destination's local map the party arrives from
+    pub travel_time: u64,       // including the delays caused by the weather
+    pub stamina_used: Vec<u32>, // by each member of the party, in the party's order
+}
+
+
+impl Biome{
+    // ticks an average party takes to cross a region of this biome, None if it can't be crossed on foot
+    pub fn travel_time(&self) -> Option<u64>{
+        match self{
+            Biome::Plains | Biome::Savanna | Biome::Island => Some(60),
+            Biome::Forest | Biome::Taiga => Some(90),
+            Biome::HotDesert | Biome::ColdDesert | Biome::Tundra => Some(120),
+            Biome::Jungle | Biome::Swamp => Some(150),
+            Biome::Glacier => Some(160),
+            Biome::Mountain => Some(180),
+            Biome::Ocean | Biome::LavaOcean | Biome::Sky => None,
+        }
+    }
+
+    // chance of an encounter while crossing a region of this biome
+    pub fn encounter_chance(&self) -> f64{
+        match self{
+            Biome::Forest | Biome::Jungle | Biome::Taiga => 0.15,
+            Biome::Mountain | Biome::Swamp => 0.12,
+            Biome::Plains | Biome::Savanna => 0.1,
+            _ => 0.05,
+        }
+    }
+
+    fn encounter_kinds(&self) -> &'static [EncounterKind]{
+        match self{
+            Biome::Plains | Biome::Savanna | Biome::Island => &[EncounterKind::Traveller, EncounterKind::Bandits, EncounterKind::Wildlife],
+            Biome::Forest | Biome::Mountain | Biome::Taiga => &[EncounterKind::Bandits, EncounterKind::Wildlife],
+            _ => &[EncounterKind::Wildlife],
+        }
+    }
+}
+
+
+impl Edge{
+    // edge of a region entered by moving from `from` to the neighbouring region `to`
+    pub fn entered_from(from: (u32, u32), to: (u32, u32)) -> Edge{
+        let (dx, dy) = (to.0 as i64 - from.0 as i64, to.1 as i64 - from.1 as i64);
+        if dx.abs() >= dy.abs(){
+            if dx > 0 { Edge::West } else { Edge::East }
+        }
+        else if dy > 0{
+            Edge::North
+        }
+        else{
+            Edge::South
+        }
+    }
+}
+
+
+// cheapest route on the biome grid between two locations, moving in 8 directions
+pub fn find_route(map: &Map, from: &Location, to: &Location) -> Option<Route>{
+    let start = (from.x, from.y);
+    let goal = (to.x, to.y);
+    map.biome_at(start.0, start.1)?.travel_time()?;
+
+    let mut best: HashMap<(u32, u32), u64> = HashMap::from([(start, 0)]);
+    let mut came_from: HashMap<(u32, u32), (u32, u32)> = HashMap::new();
+    let mut open = BinaryHeap::from([Reverse((0, start))]);
+
+    while let Some(Reverse((time, region))) = open.pop(){
+        if region == goal{
+            let mut regions = vec![goal];
+            while let Some(previous) = came_from.get(regions.last().unwrap()){
+                regions.push(*previous);
+            }
+            regions.reverse();
+            return Some(Route{ regions, base_time: time });
+        }
+        if best.get(&region).is_some_and(|best_time| time > *best_time){
+            continue;
+        }
+        for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0), (1, -1), (1, 1), (-1, 1), (-1, -1)]{
+            let (x, y) = (region.0 as i64 + dx, region.1 as i64 + dy);
+            if x < 0 || y < 0{
+                continue;
+            }
+            let neighbour = (x as u32, y as u32);
+            let Some(crossing) = map.biome_at(neighbour.0, neighbour.1).and_then(|biome| biome.travel_time()) else{
+                continue;
+            };
+            // half the time leaving a region and half entering the next one
+            let here = map.biome_at(region.0, region.1).and_then(|biome| biome.travel_time()).unwrap_or(0);
+            let step = (here + crossing) / 2;
+            let step = if dx != 0 && dy != 0 { step * 14 / 10 } else { step };
+            let new_time = time + step;
+            if best.get(&neighbour).is_none_or(|best_time| new_time < *best_time){
+                best.insert(neighbour, new_time);
+                came_from.insert(neighbour, region);
+                open.push(Reverse((new_time, neighbour)));
+            }
+        }
+    }
+    None
+}
+
+
+// a party goes as fast as its slowest member
+pub fn party_travel_time(route: &Route, party: &[&Actor]) -> u64{
+    let slowest = party.iter().map(|actor| actor.get_stats().agility.max_sprint_speed).min().unwrap_or(REFERENCE_SPEED).max(1);
+    route.base_time * REFERENCE_SPEED as u64 / slowest as u64
+}
+
+
+// moves the party along the route, advancing the clock and rolling for encounters in every region crossed,
+// bad weather in a region makes crossing it slower and more tiring
+pub fn travel(map: &Map, route: &Route, party: &[&Actor], clock: &mut WorldClock, rng: &mut impl Rng) -> Journey{
+    let fair_weather_time = party_travel_time(route, party);
+    let mut travel_time = 0;
+    let mut stamina_used = vec![0.0; party.len()];
+    // the time is split evenly between the crossings from a region to the next
+    let crossings = route.regions.len().saturating_sub(1) as u64;
+    if let (0, Some(region)) = (crossings, route.regions.first()){
+        travel_time += walk(map, *region, fair_weather_time, party, clock, &mut stamina_used);
+    }
+    let mut elapsed = 0;
+    let mut encounters = Vec::new();
+    for (index, region) in route.regions.iter().enumerate(){
+        if index > 0{
+            let reached = fair_weather_time * index as u64 / crossings;
+            travel_time += walk(map, *region, reached - elapsed, party, clock, &mut stamina_used);
+            elapsed = reached;
+        }
+        let Some(biome) = map.biome_at(region.0, region.1) else{
+            continue;
+        };
+        if index > 0 && index < route.regions.len() - 1 && rng.gen_bool(biome.encounter_chance()){
+            let kinds = biome.encounter_kinds();
+            encounters.push(Encounter{
+                kind: kinds[rng.gen_range(0..kinds.len())],
+                region: *region,
+                clock: *clock,
+            });
+        }
+    }
+    let arrival_edge = match route.regions.as_slice(){
+        [.., previous, last] => Edge::entered_from(*previous, *last),
+        _ => Edge::South,
+    };
+    Journey{
+        encounters,
+        arrival_edge,
+        travel_time,
+        stamina_used: stamina_used.into_iter().map(|stamina: f32| stamina.round() as u32).collect(),
+    }
+}
+
+
+// the party walks into a region for what would take some ticks in fair weather, returns the ticks it actually took
+fn walk(map: &Map, region: (u32, u32), ticks: u64, party: &[&Actor], clock: &mut WorldClock, stamina_used: &mut [f32]) -> u64{
+    let weather = map.weather_at(region.0, region.1, clock).unwrap_or(Weather::Clear);
+    let ticks = weather.effects().movement(ticks);
+    for (used, actor) in stamina_used.iter_mut().zip(party){
+        *used += movement_stamina(actor, ticks, weather);
+    }
+    clock.advance(ticks);
+    ticks
+}
+
+
+// passable tile on the edge of a local map closest to its middle, where travellers arrive
+pub fn entry_point(local_map: &LocalMap, edge: Edge) -> Option<Coordinates>{
+    let (width, height) = (local_map.width(), local_map.height());
+    if width == 0 || height == 0{
+        return None;
+    }
+    let along_edge = |offset: u32| match edge{
+        Edge::North => Coordinates::new(offset, 0),
+        Edge::South => Coordinates::new(offset, height - 1),
+        Edge::West => Coordinates::new(0, offset),
+        Edge::East => Coordinates::new(width - 1, offset),
+    };
+    let length = if matches!(edge, Edge::North | Edge::South) { width } else { height };
+    let middle = length / 2;
+    (0..length)
+        .map(along_edge)
+        .filter(|position| local_map.movement_cost(*position).is_some())
+        .min_by_key(|position| match edge{
+            Edge::North | Edge::South => position.x.abs_diff(middle),
+            Edge::West | Edge::East => position.y.abs_diff(middle),
+        })
+}
+
+
+// edge of the local map the position is on, actors standing there can leave the map
+pub fn exit_edge(local_map: &LocalMap, position: Coordinates) -> Option<Edge>{
+    if !local_map.contains(position){
+        return None;
+    }
+    if position.y == 0{
+        Some(Edge::North)
+    }
+    else if position.y == local_map.height() - 1{
+        Some(Edge::South)
+    }
+    else if position.x == 0{
+        Some(Edge::West)
+    }
+    else if position.x == local_map.width() - 1{
+        Some(Edge::East)
+    }
+    else{
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::actors::actor::Body;
+    use crate::common::stats::Stats;
+    use crate::objects::object::Object;
+    use crate::world::world_elements::{TileType, WorldObject, WorldObjectType};
+
+    use super::*;
+
+    fn traveller(speed: u32) -> Actor{
+        let mut stats = Stats::new_zero();
+        stats.agility.max_sprint_speed = speed;
+        Actor::new(Body::new(vec![], 180, 70), stats)
+    }
+
+    fn location(name: &str, x: u32, y: u32) -> Location{
+        Location::new(String::from(name), String::from("A place"), x, y)
+    }
+
+    // two towns on plains separated by a lake with a forest on one side and mountains on the other
+    fn world() -> (Map, Location, Location){
+        let mut map = Map::new(10, 5, Biome::Plains, 3);
+        for y in 1..4{
+            for x in 3..7{
+                map.set_biome(x, y, Biome::Ocean);
+            }
+        }
+        for x in 2..8{
+            map.set_biome(x, 0, Biome::Mountain);
+            map.set_biome(x, 4, Biome::Forest);
+        }
+        let (village, town) = (location("Village", 1, 2), location("Town", 8, 2));
+        map.add_location(village.clone(), LocalMap::new(20, 20, TileType::Grass));
+        map.add_location(town.clone(), LocalMap::new(20, 20, TileType::Grass));
+        (map, village, town)
+    }
+
+    #[test]
+    fn test_find_route_avoids_water_and_mountains() {
+        let (map, village, town) = world();
+        let route = find_route(&map, &village, &town).unwrap();
+        assert_eq!(route.regions.first(), Some(&(1, 2)));
+        assert_eq!(route.regions.last(), Some(&(8, 2)));
+        assert!(route.regions.iter().all(|(x, y)| map.biome_at(*x, *y) != Some(Biome::Ocean)));
+        assert!(route.regions.iter().any(|(_, y)| *y == 4));
+        assert_eq!(map.locations().count(), 2);
+    }
+
+    #[test]
+    fn test_slowest_member_sets_the_pace() {
+        let (map, village, town) = world();
+        let route = find_route(&map, &village, &town).unwrap();
+        let (fast, slow) = (traveller(10), traveller(2));
+        assert!(party_travel_time(&route, &[&fast]) < party_travel_time(&route, &[&fast, &slow]));
+        assert_eq!(party_travel_time(&route, &[&traveller(REFERENCE_SPEED)]), route.base_time);
+    }
+
+    #[test]
+    fn test_travel() {
+        let (map, village, town) = world();
+        let route = find_route(&map, &village, &town).unwrap();
+        let party = traveller(5);
+        let mut clock = WorldClock::at(0, 8, 0);
+        let mut rng = StdRng::seed_from_u64(1);
+        let journey = travel(&map, &route, &[&party], &mut clock, &mut rng);
+        assert_eq!(clock.ticks(), WorldClock::at(0, 8, 0).ticks() + journey.travel_time);
+        assert!(journey.travel_time >= party_travel_time(&route, &[&party]));
+        assert_eq!(journey.stamina_used.len(), 1);
+        let short = Route{ regions: vec![(1, 2)], base_time: 30 };
+        let before = clock.ticks();
+        let journey_inside = travel(&map, &short, &[&party], &mut clock, &mut rng);
+        assert_eq!(clock.ticks(), before + journey_inside.travel_time);
+        assert!(journey.encounters.iter().all(|encounter| route.regions.contains(&encounter.region)));
+
+        let local_map = map.local_map(&town).unwrap();
+        let arrival = entry_point(local_map, journey.arrival_edge).unwrap();
+        assert_eq!(exit_edge(local_map, arrival), Some(journey.arrival_edge));
+    }
+
+    #[test]
+    fn test_bad_weather_slows_the_party() {
+        let (map, village, town) = world();
+        let route = find_route(&map, &village, &town).unwrap();
+        let mut party = traveller(5);
+        party.base_stats_mut().endurance.base_stamina_used_for_movement = 3;
+        let fair_weather_time = party_travel_time(&route, &[&party]);
+        let mut rng = StdRng::seed_from_u64(1);
+        let journeys: Vec<Journey> = (0..40)
+            .map(|day| travel(&map, &route, &[&party], &mut WorldClock::at(day * 7, 8, 0), &mut rng))
+            .collect();
+        assert!(journeys.iter().all(|journey| journey.travel_time >= fair_weather_time && journey.stamina_used[0] > 0));
+        let slowest = journeys.iter().max_by_key(|journey| journey.travel_time).unwrap();
+        let fastest = journeys.iter().min_by_key(|journey| journey.travel_time).unwrap();
+        assert!(slowest.travel_time > fastest.travel_time);
+        assert!(slowest.stamina_used[0] > fastest.stamina_used[0]);
+    }
+
+    #[test]
+    fn test_entry_point_skips_blocked_tiles() {
+        let mut local_map = LocalMap::new(5, 5, TileType::Grass);
+        let rock = WorldObject::new(Object::new(1, String::from("Rock"), String::from("A big rock"), 500, 200), WorldObjectType::Rock);
+        local_map.add_object(Coordinates::new(0, 2), rock);
+        let entry = entry_point(&local_map, Edge::West).unwrap();
+        assert_eq!(entry.x, 0);
+        assert_eq!(entry.y.abs_diff(2), 1);
+        assert_eq!(exit_edge(&local_map, Coordinates::new(2, 2)), None);
+    }
+}
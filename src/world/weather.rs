@@ -0,0 +1,257 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::actors::actor::Actor;
+use crate::common::position::Coordinates;
+use crate::common::status::StatusType;
+use crate::world::physics::is_protected;
+use crate::world::time::{Season, WorldClock, TICKS_PER_HOUR};
+use crate::world::world_elements::Biome;
+
+// the weather of a region can change every this many ticks
+pub const WEATHER_PERIOD: u64 = 6 * TICKS_PER_HOUR;
+
+// how long a status inflicted by the weather lasts
+const WEATHER_STATUS_DURATION: u32 = 5;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Weather{
+    Clear,
+    Rain,
+    Snow,
+    Sandstorm,
+    Fog,
+    HeatWave,
+}
+
+// direction the wind blows towards, the length is the strength with 1 being a strong wind
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wind{
+    pub dx: f32,
+    pub dy: f32,
+}
+
+// multipliers applied by the weather, 1 means no effect
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeatherEffects{
+    pub visibility: f32,
+    pub ranged_accuracy: f32,
+    pub movement_cost: f32,
+    pub stamina_used: f32,
+    pub burn_chance: f32,       // chance per tick of getting Burn when unprotected
+    pub freeze_chance: f32,     // chance per tick of getting Freeze when unprotected
+}
+
+
+impl Weather{
+    pub fn effects(&self) -> WeatherEffects{
+        match self{
+            Weather::Clear => WeatherEffects::new(1.0, 1.0, 1.0, 1.0, 0.0, 0.0),
+            Weather::Rain => WeatherEffects::new(0.8, 0.85, 1.2, 1.1, 0.0, 0.0),
+            Weather::Snow => WeatherEffects::new(0.7, 0.8, 1.5, 1.3, 0.0, 0.05),
+            Weather::Sandstorm => WeatherEffects::new(0.3, 0.4, 1.6, 1.4, 0.0, 0.0),
+            Weather::Fog => WeatherEffects::new(0.4, 0.7, 1.0, 1.0, 0.0, 0.0),
+            Weather::HeatWave => WeatherEffects::new(0.9, 0.95, 1.1, 1.5, 0.05, 0.0),
+        }
+    }
+}
+
+
+impl WeatherEffects{
+    pub fn new(
+        visibility: f32,
+        ranged_accuracy: f32,
+        movement_cost: f32,
+        stamina_used: f32,
+        burn_chance: f32,
+        freeze_chance: f32,
+    ) -> WeatherEffects{
+        WeatherEffects{
+            visibility,
+            ranged_accuracy,
+            movement_cost,
+            stamina_used,
+            burn_chance,
+            freeze_chance,
+        }
+    }
+
+    // a movement cost or time in fair weather turned into the one in this weather
+    pub fn movement(&self, cost: u64) -> u64{
+        (cost as f32 * self.movement_cost).round() as u64
+    }
+}
+
+
+impl Wind{
+    pub fn new(dx: f32, dy: f32) -> Wind{
+        Wind{
+            dx,
+            dy,
+        }
+    }
+
+    pub fn calm() -> Wind{
+        Wind::new(0.0, 0.0)
+    }
+
+    pub fn strength(&self) -> f32{
+        (self.dx * self.dx + self.dy * self.dy).sqrt()
+    }
+
+    // how much of a smell or sound from `from` is carried to `to`, above 1 downwind and below 1 upwind
+    pub fn carry_factor(&self, from: Coordinates, to: Coordinates) -> f32{
+        let (x, y) = (to.x as f32 - from.x as f32, to.y as f32 - from.y as f32);
+        let distance = (x * x + y * y).sqrt();
+        if distance == 0.0{
+            return 1.0;
+        }
+        (1.0 + (self.dx * x + self.dy * y) / distance).max(0.0)
+    }
+}
+
+
+impl Biome{
+    // possible weather in a season with its relative likelihood
+    pub fn weather_weights(&self, season: Season) -> Vec<(Weather, u32)>{
+        let winter = season == Season::Winter;
+        let summer = season == Season::Summer;
+        match self{
+            Biome::HotDesert => vec![(Weather::Clear, 6), (Weather::Sandstorm, 2), (Weather::HeatWave, if summer { 4 } else { 1 })],
+            Biome::ColdDesert | Biome::Tundra | Biome::Glacier => vec![(Weather::Clear, 4), (Weather::Snow, if winter { 6 } else { 3 }), (Weather::Fog, 1)],
+            Biome::Ocean | Biome::Island => vec![(Weather::Clear, 5), (Weather::Rain, 3), (Weather::Fog, 2)],
+            Biome::Swamp => vec![(Weather::Clear, 3), (Weather::Rain, 3), (Weather::Fog, 4)],
+            Biome::Jungle => vec![(Weather::Clear, 3), (Weather::Rain, 6), (Weather::Fog, 1), (Weather::HeatWave, if summer { 1 } else { 0 })],
+            Biome::Mountain | Biome::Taiga => vec![(Weather::Clear, 4), (if winter { Weather::Snow } else { Weather::Rain }, 4), (Weather::Fog, 2)],
+            Biome::Savanna => vec![(Weather::Clear, 6), (Weather::Rain, if summer { 1 } else { 3 }), (Weather::HeatWave, if summer { 3 } else { 1 })],
+            Biome::Plains | Biome::Forest => vec![(Weather::Clear, 6), (if winter { Weather::Snow } else { Weather::Rain }, 3), (Weather::Fog, 1), (Weather::HeatWave, if summer { 1 } else { 0 })],
+            Biome::LavaOcean => vec![(Weather::Clear, 3), (Weather::HeatWave, 5)],
+            Biome::Sky => vec![(Weather::Clear, 6), (Weather::Rain, 2), (Weather::Fog, 2)],
+        }
+    }
+}
+
+
+// weather of the region at (x, y) of a world map at the given time, the same seed always gives the same weather
+pub fn weather_at(seed: u64, biome: Biome, x: u32, y: u32, clock: &WorldClock) -> Weather{
+    let period = clock.ticks() / WEATHER_PERIOD;
+    let mut rng = StdRng::seed_from_u64(mix(mix(mix(seed, x as u64), y as u64), period));
+    let weights = biome.weather_weights(clock.season());
+    let total: u32 = weights.iter().map(|(_, weight)| weight).sum();
+    let mut roll = rng.gen_range(0..total);
+    for (weather, weight) in weights{
+        if roll < weight{
+            return weather;
+        }
+        roll -= weight;
+    }
+    Weather::Clear
+}
+
+
+// wind of the region at (x, y) of a world map at the given time, changes with the weather
+pub fn wind_at(seed: u64, x: u32, y: u32, clock: &WorldClock) -> Wind{
+    let period = clock.ticks() / WEATHER_PERIOD;
+    let mut rng = StdRng::seed_from_u64(mix(mix(mix(seed, x as u64), y as u64), !period));
+    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+    let strength = rng.gen_range(0.0..1.0);
+    Wind::new(angle.cos() * strength, angle.sin() * strength)
+}
+
+
+// applies one tick of the weather to an actor caught outside in it
+pub fn apply_weather(actor: &mut Actor, weather: Weather, rng: &mut impl Rng){
+    let effects = weather.effects();
+    if effects.burn_chance > 0.0 && !is_protected(actor, StatusType::Burn) && rng.gen_bool(effects.burn_chance as f64){
+        actor.add_status(StatusType::Burn, WEATHER_STATUS_DURATION);
+    }
+    if effects.freeze_chance > 0.0 && !is_protected(actor, StatusType::Freeze) && rng.gen_bool(effects.freeze_chance as f64){
+        actor.add_status(StatusType::Freeze, WEATHER_STATUS_DURATION);
+    }
+}
+
+
+// stamina an actor uses moving for some ticks in the weather
+pub fn movement_stamina(actor: &Actor, ticks: u64, weather: Weather) -> f32{
+    let per_hour = actor.get_stats().endurance.base_stamina_used_for_movement as f32;
+    per_hour * ticks as f32 / TICKS_PER_HOUR as f32 * weather.effects().stamina_used
+}
+
+
+// splitmix64 step, used to turn the seed and coordinates into a seed for the region
+fn mix(seed: u64, value: u64) -> u64{
+    let mut z = seed ^ value.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::actors::actor::Body;
+    use crate::common::stats::Stats;
+    use crate::world::time::DAYS_PER_SEASON;
+
+    use super::*;
+
+    #[test]
+    fn test_weather_is_deterministic() {
+        let clock = WorldClock::at(12, 8, 0);
+        assert_eq!(weather_at(42, Biome::Plains, 3, 4, &clock), weather_at(42, Biome::Plains, 3, 4, &clock));
+    }
+
+    #[test]
+    fn test_weather_follows_biome() {
+        for day in 0..100{
+            let clock = WorldClock::at(day, 12, 0);
+            let weather = weather_at(7, Biome::HotDesert, 0, 0, &clock);
+            assert!(matches!(weather, Weather::Clear | Weather::Sandstorm | Weather::HeatWave));
+        }
+    }
+
+    #[test]
+    fn test_snow_only_in_winter_on_plains() {
+        for day in 0..DAYS_PER_SEASON{
+            let clock = WorldClock::at(day, 12, 0);
+            assert_ne!(weather_at(7, Biome::Plains, 0, 0, &clock), Weather::Snow);
+        }
+        let snows_in_winter = (0..DAYS_PER_SEASON)
+            .map(|day| WorldClock::at(DAYS_PER_SEASON * 3 + day, 12, 0))
+            .any(|clock| weather_at(7, Biome::Plains, 0, 0, &clock) == Weather::Snow);
+        assert!(snows_in_winter);
+    }
+
+    #[test]
+    fn test_wind_carry_factor() {
+        let wind = Wind::new(1.0, 0.0);
+        let (west, east) = (Coordinates::new(0, 0), Coordinates::new(5, 0));
+        assert_eq!(wind.carry_factor(west, east), 2.0);
+        assert_eq!(wind.carry_factor(east, west), 0.0);
+        assert_eq!(Wind::calm().carry_factor(west, east), 1.0);
+        assert!(wind_at(1, 0, 0, &WorldClock::at(0, 0, 0)).strength() <= 1.0);
+    }
+
+    #[test]
+    fn test_apply_weather() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut actor = Actor::new(Body::new(vec![], 180, 70), Stats::new_zero());
+        for _ in 0..200{
+            apply_weather(&mut actor, Weather::Snow, &mut rng);
+        }
+        assert!(actor.has_status(StatusType::Freeze));
+        assert!(!actor.has_status(StatusType::Burn));
+    }
+
+    #[test]
+    fn test_weather_slows_and_tires() {
+        let mut stats = Stats::new_zero();
+        stats.endurance.base_stamina_used_for_movement = 4;
+        let actor = Actor::new(Body::new(vec![], 180, 70), stats);
+        assert_eq!(movement_stamina(&actor, 2 * TICKS_PER_HOUR, Weather::Clear), 8.0);
+        assert!(movement_stamina(&actor, 2 * TICKS_PER_HOUR, Weather::Sandstorm) > 8.0);
+        assert_eq!(Weather::Clear.effects().movement(60), 60);
+        assert_eq!(Weather::Snow.effects().movement(60), 90);
+    }
+}
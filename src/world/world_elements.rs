@@ -1,6 +1,7 @@
 use crate::objects::object::Object;
 
-enum Biome{
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Biome{
     Mountain,
     Plains,
     Forest,
@@ -18,7 +19,8 @@ enum Biome{
     Sky,
 }
 
-enum World{
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum World{
     Earth,
     Mars,
     Venus,
@@ -31,7 +33,8 @@ enum World{
     Purgatory,
 }
 
-enum TileType{
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TileType{
     Grass,
     Dirt,
     Sand,
@@ -40,25 +43,108 @@ enum TileType{
     Stone,
     Floor,
     Leaves,
+    Water,      // shallow enough to wade through
 }
 
 
 // all objects that can be found naturally in the world
-enum WorldObjectType{
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WorldObjectType{
     Tree,
     Rock,
     Bush,
     Flower,
 }
 
-enum BuildingType{
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuildingType{
     House,
     Castle,
     Tower,
     Wall,
 }
 
-struct WorldObject{
-    object: Object,
-    object_type: WorldObjectType,
-}
\ No newline at end of file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorldObject{
+    pub object: Object,
+    pub object_type: WorldObjectType,
+    pub durability: u32,    // damage it can take before being destroyed
+    pub yields: u32,        // how many more times it can be gathered from
+}
+
+
+impl TileType{
+    // cost of walking onto a tile of this type, 10 is the cost of flat, easy ground
+    pub fn movement_cost(&self) -> u32{
+        match self{
+            TileType::Grass | TileType::Dirt | TileType::Floor => 10,
+            TileType::Stone | TileType::Leaves => 12,
+            TileType::Sand => 15,
+            TileType::Snow => 20,
+            TileType::Ice => 25,
+            TileType::Water => 30,
+        }
+    }
+}
+
+
+impl WorldObjectType{
+    // extra cost of walking through a tile with this object on it, None if it can't be walked through
+    pub fn movement_cost(&self) -> Option<u32>{
+        match self{
+            WorldObjectType::Tree | WorldObjectType::Rock => None,
+            WorldObjectType::Bush => Some(10),
+            WorldObjectType::Flower => Some(0),
+        }
+    }
+
+    pub fn durability(&self) -> u32{
+        match self{
+            WorldObjectType::Tree => 100,
+            WorldObjectType::Rock => 200,
+            WorldObjectType::Bush => 10,
+            WorldObjectType::Flower => 1,
+        }
+    }
+
+    // times it can be gathered from before being depleted, 0 if it can't be gathered from
+    pub fn yields(&self) -> u32{
+        match self{
+            WorldObjectType::Bush => 3,
+            WorldObjectType::Flower => 1,
+            WorldObjectType::Tree | WorldObjectType::Rock => 0,
+        }
+    }
+
+    // how much of the sight through a tile with this object on it is blocked, 1 blocks it completely
+    pub fn opacity(&self) -> f32{
+        match self{
+            WorldObjectType::Rock => 1.0,
+            WorldObjectType::Tree => 0.5,
+            WorldObjectType::Bush => 0.3,
+            WorldObjectType::Flower => 0.0,
+        }
+    }
+
+    // loudness lost by a sound going through a tile with this object on it, on top of the distance
+    pub fn sound_damping(&self) -> f32{
+        match self{
+            WorldObjectType::Rock => 4.0,
+            WorldObjectType::Tree => 1.0,
+            WorldObjectType::Bush => 0.5,
+            WorldObjectType::Flower => 0.0,
+        }
+    }
+}
+
+
+impl WorldObject{
+    pub fn new(object: Object, object_type: WorldObjectType) -> WorldObject{
+        WorldObject{
+            object,
+            object_type,
+            durability: object_type.durability(),
+            yields: object_type.yields(),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use impact::actors::actor::{Actor, Body, BodyPart};
+use impact::actors::character::{Background, Character};
+use impact::actors::detection::DetectionTracker;
+use impact::actors::dialogue::{Branch, DialogueOption, DialogueTree, Skill};
+use impact::actors::faction::Factions;
+use impact::actors::npc::{Role, NPC};
+use impact::actors::theft::{steal, Thief, Victim};
+use impact::actors::trade::{bread, BREAD_ID};
+use impact::common::position::{XPosition, YPosition};
+use impact::common::stats::{Stats, SubStat};
+use impact::world::time::WorldClock;
+use impact::world::world_elements::World;
+
+
+#[test]
+fn test_build_character() {
+    let head = BodyPart::new(String::from("Head"), true, false, 0, 40, XPosition::Mid, YPosition::High);
+    let arm = BodyPart::new(String::from("Arm"), false, true, 0, 30, XPosition::Left, YPosition::Mid);
+    let body = Body::new(vec![head, arm], 175, 70);
+    assert_eq!(body.able_arts(), 1);
+    assert!(body.body_parts()[0].is_vital());
+
+    let character = Character::new("Brann", Background::Soldier, Actor::new(body, Stats::new_zero()));
+    assert_eq!(character.level(), 1);
+    assert_eq!(character.actor().body().body_parts()[1].name(), "Arm");
+}
+
+
+#[test]
+fn test_build_npc() {
+    let guard = NPC::new(Actor::new(Body::new(vec![], 180, 80), Stats::new_zero()), Role::Guard);
+    assert_eq!(guard.role, Role::Guard);
+    assert!(!guard.actor.is_dead());
+}
+
+
+#[test]
+fn test_actions_train_the_character() {
+    let mut stats = Stats::new_zero();
+    stats.intelligence.learning = 0.5;
+    stats.intelligence.application = 0.5;
+    let mut thief = Character::new("Vex", Background::Soldier, Actor::new(Body::new(vec![], 170, 60), stats));
+    let mut merchant = NPC::new(Actor::new(Body::new(vec![], 180, 80), Stats::new_zero()), Role::Shopkeeper);
+    let (mut tracker, mut factions) = (DetectionTracker::new(), Factions::new());
+    let mut rng = StdRng::seed_from_u64(0);
+    let clock = WorldClock::new(0);
+
+    merchant.actor.add_to_inventory(bread());
+    let (thief_id, merchant_id) = (1, 2);
+    let victim = Victim{ id: merchant_id, actor: &mut merchant.actor, faction: None };
+    let attempt = steal(Thief{ id: thief_id, actor: thief.actor_mut() }, victim, BREAD_ID, &mut tracker, &mut factions, &mut rng).unwrap();
+    assert!(thief.learn(attempt.practice, &clock) > 0.0);
+
+    let mut tree = DialogueTree::new(0);
+    tree.add_node(0, "What do you want?", vec![
+        DialogueOption::with_check("You dropped this.", Skill::Deception, 0.2, Branch::end(), Branch::end()),
+    ]);
+    let mut conversation = tree.start(thief_id, merchant_id, None);
+    let reply = conversation.choose(0, thief.actor(), &mut merchant.actor, &mut factions, &mut rng).unwrap();
+    thief.learn(reply.practice.unwrap(), &clock);
+
+    let climb = World::Earth.parameters().climb(&thief.actor().get_stats(), 0.5);
+    thief.learn(climb, &clock);
+
+    for sub_stat in [SubStat::Pickpocting, SubStat::Deception, SubStat::Climbing]{
+        assert!(thief.progression.experience(sub_stat) > 0.0);
+        assert!(thief.actor().base_stats().sub_stat(sub_stat) > 0.0);
+    }
+    assert_eq!(thief.progression.experience(SubStat::Stealth), 0.0);
+}